@@ -0,0 +1,268 @@
+use crate::engine::GameState;
+use crate::i18n::{self, tr};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+
+const TIMES_OF_DAY: [&str; 3] = ["morning", "afternoon", "night"];
+
+/// Requisitos para que una acción esté disponible.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Prerequisites {
+    pub min_affection: i32,
+    pub min_trust: i32,
+    pub min_day: u32,
+    /// Fases del día en las que se permite la acción (vacío = todas).
+    pub time: Vec<String>,
+}
+
+/// Definición de una acción que el jugador puede realizar con Sylvie.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActionDef {
+    pub id: String,
+    pub label: String,
+    #[serde(default)]
+    pub affection: i32,
+    #[serde(default)]
+    pub trust: i32,
+    pub minutes: u32,
+    #[serde(default)]
+    pub dialogue_key: String,
+    /// Si es `true`, sólo se usan los diálogos propios de la acción.
+    #[serde(default)]
+    pub exclusive_dialogue: bool,
+    #[serde(default)]
+    pub requires: Prerequisites,
+    /// Minutos de juego que deben pasar antes de repetir la acción.
+    #[serde(default)]
+    pub cooldown_minutes: u32,
+    /// Si es `true`, la acción abre una conversación de `conversations.json`.
+    #[serde(default)]
+    pub opens_conversation: bool,
+}
+
+impl ActionDef {
+    /// Comprueba requisitos y enfriamiento contra el estado actual.
+    pub fn check(&self, state: &GameState) -> Result<(), ActionError> {
+        let req = &self.requires;
+        let locked = |reason: String| Err(ActionError::Locked { id: self.id.clone(), reason });
+
+        if state.affection < req.min_affection {
+            return locked(tr!("action.needs_affection", value = req.min_affection));
+        }
+        if state.trust < req.min_trust {
+            return locked(tr!("action.needs_trust", value = req.min_trust));
+        }
+        if state.day < req.min_day {
+            return locked(tr!("action.from_day", day = req.min_day));
+        }
+        if !req.time.is_empty() && !req.time.contains(&state.time_of_day) {
+            let phases: Vec<&str> = req.time.iter().map(|t| i18n::phase(t)).collect();
+            return locked(tr!("action.only_at", phases = phases.join(", ")));
+        }
+        if self.cooldown_minutes > 0
+            && let Some(&last) = state.cooldowns.get(&self.id)
+        {
+            let elapsed = state.clock_minutes().saturating_sub(last);
+            if elapsed < self.cooldown_minutes {
+                return Err(ActionError::Cooldown {
+                    id: self.id.clone(),
+                    remaining: self.cooldown_minutes - elapsed,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Límites diarios de afecto y confianza que se pueden ganar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DailyCaps {
+    pub affection: i32,
+    pub trust: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionError {
+    Unknown(String),
+    Locked { id: String, reason: String },
+    Cooldown { id: String, remaining: u32 },
+    /// Hay una conversación abierta esperando respuesta.
+    InConversation,
+    InvalidReply(usize),
+    /// El turno se jugó pero no se pudo guardar la partida.
+    Save(String),
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::Unknown(id) => write!(f, "{}", tr!("action.unknown", id = id)),
+            ActionError::Locked { id, reason } => write!(f, "{}", tr!("action.locked", id = id, reason = reason)),
+            ActionError::Cooldown { id, remaining } => {
+                let time = format!("{}:{:02}", remaining / 60, remaining % 60);
+                write!(f, "{}", tr!("action.cooldown", id = id, time = time))
+            }
+            ActionError::InConversation => write!(f, "{}", tr!("action.in_conversation")),
+            ActionError::InvalidReply(i) => write!(f, "{}", tr!("action.invalid_reply", index = i + 1)),
+            ActionError::Save(error) => write!(f, "{}", tr!("action.save_failed", error = error)),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
+
+/// Error de validación de `actions.json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidActions(pub String);
+
+impl fmt::Display for InvalidActions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", tr!("actions.invalid", error = self.0))
+    }
+}
+
+impl std::error::Error for InvalidActions {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ActionFile {
+    daily_caps: DailyCaps,
+    actions: Vec<ActionDef>,
+}
+
+/// Registro ordenado de acciones; el orden es el del menú.
+#[derive(Debug, Clone)]
+pub struct ActionRegistry {
+    actions: Vec<ActionDef>,
+    pub caps: DailyCaps,
+}
+
+impl ActionRegistry {
+    /// Acciones por defecto, las embebidas en el ejecutable.
+    #[cfg(test)]
+    pub fn builtin() -> Self {
+        let content = crate::assets::embedded("actions.json").expect("actions.json no embebido");
+        Self::parse(std::str::from_utf8(content).unwrap()).expect("actions.json embebido inválido")
+    }
+
+    pub fn parse(content: &str) -> Result<Self, InvalidActions> {
+        let file: ActionFile =
+            serde_json::from_str(content).map_err(|e| InvalidActions(e.to_string()))?;
+        let mut registry = Self { actions: file.actions, caps: file.daily_caps };
+        registry.validate()?;
+        Ok(registry)
+    }
+
+    fn validate(&mut self) -> Result<(), InvalidActions> {
+        let invalid = |msg: String| Err(InvalidActions(msg));
+
+        if self.caps.affection <= 0 || self.caps.trust <= 0 {
+            return invalid(tr!("actions.caps_not_positive").to_string());
+        }
+        if self.actions.is_empty() {
+            return invalid(tr!("actions.none").to_string());
+        }
+
+        let mut seen = HashSet::new();
+        for action in &mut self.actions {
+            if action.id.is_empty() || action.label.is_empty() {
+                return invalid(tr!("actions.missing_id").to_string());
+            }
+            if !seen.insert(action.id.clone()) {
+                return invalid(tr!("actions.duplicate", id = action.id));
+            }
+            if action.minutes == 0 {
+                return invalid(tr!("actions.zero_minutes", id = action.id));
+            }
+            if let Some(t) = action.requires.time.iter().find(|t| !TIMES_OF_DAY.contains(&t.as_str())) {
+                return invalid(tr!("actions.unknown_phase", id = action.id, phase = t));
+            }
+            if action.dialogue_key.is_empty() {
+                action.dialogue_key = action.id.clone();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Result<&ActionDef, ActionError> {
+        self.actions
+            .iter()
+            .find(|a| a.id == id)
+            .ok_or_else(|| ActionError::Unknown(id.to_string()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ActionDef> {
+        self.actions.iter()
+    }
+
+    /// Traduce las etiquetas con las claves `action_label.<id>` del catálogo del idioma actual.
+    pub fn localize(&mut self) {
+        for action in &mut self.actions {
+            if let Some(label) = i18n::data_text(&format!("action_label.{}", action.id)) {
+                action.label = label.to_string();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_actions_are_valid() {
+        let registry = ActionRegistry::builtin();
+        assert_eq!(registry.caps, DailyCaps { affection: 10, trust: 5 });
+        assert_eq!(registry.get("talk").unwrap().dialogue_key, "talk");
+        assert!(registry.get("give_treat").unwrap().exclusive_dialogue);
+        assert!(registry.get("talk").unwrap().opens_conversation);
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let duplicated = r#"{
+            "daily_caps": { "affection": 10, "trust": 5 },
+            "actions": [
+                { "id": "talk", "label": "Hablar", "minutes": 60 },
+                { "id": "talk", "label": "Hablar", "minutes": 60 }
+            ]
+        }"#;
+        assert!(ActionRegistry::parse(duplicated).unwrap_err().0.contains("duplicada"));
+
+        let bad_time = r#"{
+            "daily_caps": { "affection": 10, "trust": 5 },
+            "actions": [
+                { "id": "sleep", "label": "Dormir", "minutes": 60, "requires": { "time": ["evening"] } }
+            ]
+        }"#;
+        assert!(ActionRegistry::parse(bad_time).unwrap_err().0.contains("evening"));
+    }
+
+    #[test]
+    fn test_prerequisites_and_cooldown() {
+        let registry = ActionRegistry::parse(r#"{
+            "daily_caps": { "affection": 10, "trust": 5 },
+            "actions": [
+                { "id": "walk", "label": "Pasear", "minutes": 60,
+                  "requires": { "min_trust": 5, "time": ["afternoon"] },
+                  "cooldown_minutes": 300 }
+            ]
+        }"#).unwrap();
+        let walk = registry.get("walk").unwrap();
+
+        let mut state = GameState::default();
+        assert!(matches!(walk.check(&state), Err(ActionError::Locked { .. })));
+
+        state.trust = 5;
+        state.hour = 13;
+        state.time_of_day = "afternoon".to_string();
+        assert_eq!(walk.check(&state), Ok(()));
+
+        state.cooldowns.insert("walk".to_string(), state.clock_minutes() - 60);
+        assert_eq!(walk.check(&state), Err(ActionError::Cooldown { id: "walk".to_string(), remaining: 240 }));
+    }
+}
@@ -0,0 +1,128 @@
+//! Recursos del juego compilados en el ejecutable.
+//!
+//! Cualquier archivo presente en el directorio de datos sustituye a la copia
+//! embebida del mismo nombre, así que el binario funciona sin `modules/`.
+//!
+//! Los textos del juego tienen una versión por idioma (`dialogues.es.json`,
+//! `dialogues.en.json`...), que se lee con `load_localized`.
+
+use crate::i18n::{self, tr};
+use crate::paths::Paths;
+use std::borrow::Cow;
+use std::io;
+use std::path::PathBuf;
+
+const EMBEDDED: [(&str, &[u8]); 10] = [
+    ("dialogues.es.json", include_bytes!("../modules/dialogues.es.json")),
+    ("dialogues.en.json", include_bytes!("../modules/dialogues.en.json")),
+    ("actions.json", include_bytes!("../modules/actions.json")),
+    ("conversations.es.json", include_bytes!("../modules/conversations.es.json")),
+    ("conversations.en.json", include_bytes!("../modules/conversations.en.json")),
+    ("locales/es.json", include_bytes!("../modules/locales/es.json")),
+    ("locales/en.json", include_bytes!("../modules/locales/en.json")),
+    ("audio.json", include_bytes!("../modules/audio.json")),
+    ("art/sprites.json", include_bytes!("../modules/art/sprites.json")),
+    ("art/Sylvie-base.png", include_bytes!("../modules/art/Sylvie-base.png")),
+];
+
+/// Contenido de un recurso y de dónde salió (para los mensajes de error).
+pub struct Asset {
+    pub bytes: Cow<'static, [u8]>,
+    pub origin: PathBuf,
+}
+
+impl Asset {
+    pub fn text(&self) -> Result<&str, io::Error> {
+        std::str::from_utf8(&self.bytes).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", self.origin.display(), e))
+        })
+    }
+}
+
+pub fn embedded(name: &str) -> Option<&'static [u8]> {
+    EMBEDDED.iter().find(|(n, _)| *n == name).map(|(_, bytes)| *bytes)
+}
+
+/// Nombres de todos los recursos embebidos.
+pub fn embedded_names() -> impl Iterator<Item = &'static str> {
+    EMBEDDED.iter().map(|(name, _)| *name)
+}
+
+/// Lee `name` del directorio de datos o, si no existe ahí, la copia embebida.
+pub fn load(paths: &Paths, name: &str) -> Result<Asset, io::Error> {
+    let path = paths.data_file(name);
+    if path.exists() {
+        return Ok(Asset { bytes: Cow::Owned(std::fs::read(&path)?), origin: path });
+    }
+    embedded(name)
+        .map(|bytes| Asset { bytes: Cow::Borrowed(bytes), origin: embedded_origin(name) })
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, tr!("assets.not_found", path = path.display())))
+}
+
+/// Como `load`, pero sin textos traducidos: `i18n` lo usa para leer los catálogos
+/// antes de tenerlos. `None` si el recurso no existe.
+pub fn read(paths: &Paths, name: &str) -> Result<Option<Cow<'static, [u8]>>, io::Error> {
+    let path = paths.data_file(name);
+    if path.exists() {
+        return Ok(Some(Cow::Owned(std::fs::read(&path)?)));
+    }
+    Ok(embedded(name).map(Cow::Borrowed))
+}
+
+/// Origen que se muestra para un recurso embebido (`<embebido>/actions.json`).
+fn embedded_origin(name: &str) -> PathBuf {
+    PathBuf::from(tr!("assets.embedded")).join(name)
+}
+
+/// Nombre de la versión de `name` en `locale`: `dialogues.json` → `dialogues.en.json`.
+pub fn localized_name(name: &str, locale: &str) -> String {
+    match name.rsplit_once('.') {
+        Some((stem, ext)) => format!("{}.{}.{}", stem, locale, ext),
+        None => format!("{}.{}", name, locale),
+    }
+}
+
+/// Lee la versión de `name` en `locale`, o `None` si no hay ninguna. Para el idioma
+/// por defecto también vale el nombre sin sufijo (`dialogues.json`) en el
+/// directorio de datos, como antes de que hubiera traducciones.
+pub fn load_localized(paths: &Paths, name: &str, locale: &str) -> Result<Option<Asset>, io::Error> {
+    let file = localized_name(name, locale);
+    let mut candidates = vec![file.as_str()];
+    if locale == i18n::DEFAULT_LOCALE {
+        candidates.push(name);
+    }
+    if let Some(found) = candidates.into_iter().find(|c| paths.data_file(c).exists()) {
+        return load(paths, found).map(Some);
+    }
+    Ok(embedded(&file).map(|bytes| Asset { bytes: Cow::Borrowed(bytes), origin: embedded_origin(&file) }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_overrides_embedded() {
+        let dir = std::env::temp_dir().join("tfeeling_test_assets");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = Paths { data_dir: dir.clone(), save_dir: dir.join("saves"), config_file: dir.join("config.toml"), cache_dir: dir.join("cache") };
+
+        let asset = load(&paths, "actions.json").unwrap();
+        assert!(asset.origin.starts_with("<embebido>"));
+        assert!(asset.text().unwrap().contains("pat_head"));
+
+        std::fs::write(dir.join("actions.json"), "{}").unwrap();
+        let asset = load(&paths, "actions.json").unwrap();
+        assert_eq!(asset.origin, dir.join("actions.json"));
+        assert_eq!(asset.text().unwrap(), "{}");
+
+        assert!(load(&paths, "nada.json").is_err());
+
+        // El nombre sin idioma sólo sustituye a la versión del idioma por defecto
+        std::fs::write(dir.join("dialogues.json"), "{}").unwrap();
+        assert_eq!(load_localized(&paths, "dialogues.json", "es").unwrap().unwrap().origin, dir.join("dialogues.json"));
+        assert!(load_localized(&paths, "dialogues.json", "en").unwrap().unwrap().origin.starts_with("<embebido>"));
+        assert!(load_localized(&paths, "dialogues.json", "fr").unwrap().is_none());
+    }
+}
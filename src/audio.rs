@@ -0,0 +1,274 @@
+//! Música de fondo y efectos de sonido.
+//!
+//! `modules/audio.json` asigna una pista a cada fase del día y un efecto a cada
+//! acción. Los efectos pueden ser archivos o sonidos sintetizados (`synth:<nombre>`).
+//! Los archivos que falten se ignoran, y si no hay dispositivo de audio (por
+//! ejemplo, en un servidor sin tarjeta de sonido) el juego sigue en silencio.
+
+use crate::assets;
+use crate::config::AudioConfig;
+use crate::dialogue::{parse_json, DialogueLoadError};
+use crate::i18n::tr;
+use crate::paths::Paths;
+use rand::Rng;
+use rodio::source::SineWave;
+use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink, Source};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::path::Path;
+use std::time::Duration;
+
+/// Duración de cada pitido de voz; menor que un paso de la animación.
+const BLIP_LENGTH: Duration = Duration::from_millis(20);
+
+/// Efectos incluidos, sin archivos: notas (Hz, ms) que suenan una tras otra.
+const SYNTH_EFFECTS: &[(&str, &[(f32, u64)])] = &[
+    ("pat", &[(660.0, 60), (880.0, 110)]),
+    ("treat", &[(523.3, 70), (659.3, 70), (784.0, 140)]),
+    ("talk", &[(392.0, 50), (440.0, 50), (392.0, 80)]),
+];
+
+/// Notas del efecto sintetizado si `file` es `synth:<nombre>`.
+fn synth_notes(file: &str) -> Option<&'static [(f32, u64)]> {
+    let name = file.strip_prefix("synth:")?;
+    SYNTH_EFFECTS.iter().find(|(n, _)| *n == name).map(|(_, notes)| *notes)
+}
+
+/// Contenido de `modules/audio.json`. Las rutas son relativas al directorio de datos.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AudioManifest {
+    /// Pista por fase del día (`morning`, `afternoon`, `night`).
+    #[serde(default)]
+    pub music: BTreeMap<String, String>,
+    /// Efecto por id de acción.
+    #[serde(default)]
+    pub effects: BTreeMap<String, String>,
+}
+
+/// Tono base en Hz: más grave cuando desconfía, más agudo cuando está a gusto.
+fn blip_pitch(mood: &str) -> f32 {
+    match mood {
+        "distrust" => 320.0,
+        "trust" => 620.0,
+        _ => 460.0,
+    }
+}
+
+/// Tono del pitido para `c`, o `None` si no suena (espacios y puntuación).
+/// `jitter`, entre 0 y 1, varía el tono un ±5 % para que no suene mecánico.
+fn blip_frequency(c: char, mood: &str, jitter: f32) -> Option<f32> {
+    if !c.is_alphanumeric() {
+        return None;
+    }
+    Some(blip_pitch(mood) * (0.95 + 0.1 * jitter.clamp(0.0, 1.0)))
+}
+
+impl AudioManifest {
+    pub fn parse(content: &str, path: &Path) -> Result<Self, DialogueLoadError> {
+        parse_json(content, path)
+    }
+}
+
+/// Salida abierta: el stream debe vivir mientras suene algo.
+struct Output {
+    stream: OutputStream,
+    music: Sink,
+    voice: Sink,
+}
+
+pub struct Audio {
+    output: Option<Output>,
+    manifest: AudioManifest,
+    paths: Paths,
+    volume: f32,
+    muted: bool,
+    voice: bool,
+    /// Fase cuya pista está sonando.
+    phase: Option<String>,
+}
+
+impl Audio {
+    /// Carga el manifiesto y abre el dispositivo por defecto; sin dispositivo, queda en silencio.
+    pub fn new(paths: &Paths, config: &AudioConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let asset = assets::load(paths, "audio.json")?;
+        let manifest = AudioManifest::parse(asset.text()?, &asset.origin)?;
+        let output = OutputStreamBuilder::open_default_stream().ok().map(|mut stream| {
+            stream.log_on_drop(false);
+            let music = Sink::connect_new(stream.mixer());
+            let voice = Sink::connect_new(stream.mixer());
+            Output { stream, music, voice }
+        });
+        Ok(Self::with_output(output, manifest, paths, config))
+    }
+
+    fn with_output(output: Option<Output>, manifest: AudioManifest, paths: &Paths, config: &AudioConfig) -> Self {
+        let mut audio = Self {
+            output,
+            manifest,
+            paths: paths.clone(),
+            volume: 0.0,
+            muted: false,
+            voice: false,
+            phase: None,
+        };
+        audio.configure(config);
+        audio
+    }
+
+    /// Aplica las preferencias de sonido.
+    pub fn configure(&mut self, config: &AudioConfig) {
+        self.volume = config.volume.clamp(0.0, 1.0);
+        self.muted = config.muted;
+        self.voice = config.voice;
+        if !self.voice {
+            self.stop_voice();
+        }
+        self.apply_volume();
+    }
+
+    /// Cambia la pista de fondo si la fase del día es otra.
+    pub fn set_phase(&mut self, phase: &str) {
+        if self.phase.as_deref() == Some(phase) {
+            return;
+        }
+        self.phase = Some(phase.to_string());
+        let track = self.manifest.music.get(phase).and_then(|file| self.read(file));
+        if let Some(output) = &self.output {
+            output.music.clear();
+            if let Some(bytes) = track
+                && let Ok(source) = Decoder::new_looped(Cursor::new(bytes))
+            {
+                output.music.append(source);
+                output.music.play();
+            }
+        }
+    }
+
+    /// Reproduce el efecto asociado a la acción, si lo hay.
+    pub fn effect(&self, action_id: &str) {
+        if self.muted {
+            return;
+        }
+        let Some(output) = &self.output else { return };
+        let Some(file) = self.manifest.effects.get(action_id) else { return };
+        if let Some(notes) = synth_notes(file) {
+            let mut start = Duration::ZERO;
+            for &(pitch, ms) in notes {
+                let length = Duration::from_millis(ms);
+                let note = SineWave::new(pitch).take_duration(length).fade_out(length).amplify(self.volume * 0.25);
+                output.stream.mixer().add(note.delay(start));
+                start += length;
+            }
+            return;
+        }
+        let Some(bytes) = self.read(file) else { return };
+        if let Ok(source) = Decoder::new(Cursor::new(bytes)) {
+            output.stream.mixer().add(source.amplify(self.volume));
+        }
+    }
+
+    /// Pitido de voz para un carácter recién mostrado. Los espacios y la
+    /// puntuación no suenan; el tono depende del ánimo de Sylvie.
+    pub fn blip(&self, c: char, mood: &str) {
+        if self.muted || !self.voice {
+            return;
+        }
+        let Some(output) = &self.output else { return };
+        let Some(pitch) = blip_frequency(c, mood, rand::rng().random()) else { return };
+        output.voice.append(
+            SineWave::new(pitch)
+                .take_duration(BLIP_LENGTH)
+                .fade_out(BLIP_LENGTH)
+                .amplify(self.volume * 0.2),
+        );
+    }
+
+    /// Corta los pitidos pendientes (al saltar la animación).
+    pub fn stop_voice(&self) {
+        if let Some(output) = &self.output {
+            output.voice.clear();
+            output.voice.play();
+        }
+    }
+
+    /// Texto para la barra de estado.
+    pub fn label(&self) -> String {
+        if self.output.is_none() {
+            tr!("audio.unavailable").to_string()
+        } else if self.muted {
+            tr!("audio.muted").to_string()
+        } else {
+            tr!("audio.volume", percent = format!("{:.0}", self.volume * 100.0))
+        }
+    }
+
+    fn apply_volume(&self) {
+        if self.muted {
+            self.stop_voice();
+        }
+        if let Some(output) = &self.output {
+            output.music.set_volume(if self.muted { 0.0 } else { self.volume });
+        }
+    }
+
+    fn read(&self, file: &str) -> Option<Vec<u8>> {
+        assets::load(&self.paths, file).ok().map(|asset| asset.bytes.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_silent_without_device() {
+        let manifest = AudioManifest::parse(r#"{ "music": { "morning": "audio/morning.ogg" }, "effects": { "pat_head": "audio/pat.ogg" } }"#, Path::new("audio.json")).unwrap();
+        let dir = std::env::temp_dir().join("tfeeling_test_audio");
+        let paths = Paths { data_dir: dir.clone(), save_dir: dir.clone(), config_file: dir.join("config.toml"), cache_dir: dir.join("cache") };
+        let mut audio = Audio::with_output(None, manifest, &paths, &AudioConfig::default());
+
+        // Sin dispositivo ni archivos, nada falla
+        audio.set_phase("morning");
+        audio.effect("pat_head");
+        audio.configure(&AudioConfig { volume: 3.0, ..Default::default() });
+        assert_eq!(audio.volume, 1.0);
+        assert_eq!(audio.label(), "Sin audio");
+        audio.blip('a', "trust");
+        audio.stop_voice();
+
+        // El manifiesto embebido no puede nombrar archivos que no se distribuyen,
+        // y las acciones incluidas suenan sin instalar nada
+        let bundled = AudioManifest::parse(include_str!("../modules/audio.json"), Path::new("audio.json")).unwrap();
+        for file in bundled.music.values().chain(bundled.effects.values()) {
+            assert!(assets::embedded(file).is_some() || synth_notes(file).is_some(), "{} no está embebido", file);
+        }
+        for action in crate::action::ActionRegistry::builtin().iter() {
+            assert!(bundled.effects.contains_key(&action.id), "{} no tiene efecto", action.id);
+        }
+        assert!(synth_notes("synth:pat").is_some());
+        assert_eq!(synth_notes("synth:nada"), None);
+        assert_eq!(synth_notes("audio/pat.ogg"), None);
+
+        let err = AudioManifest::parse(r#"{ "volume": 0.5 }"#, Path::new("audio.json")).unwrap_err();
+        assert_eq!(err.key, "volume");
+    }
+
+    #[test]
+    fn test_blip_frequency() {
+        // Espacios y puntuación no suenan
+        for c in [' ', '.', ',', '!', '¿', '…'] {
+            assert_eq!(blip_frequency(c, "neutral", 0.5), None);
+        }
+        assert_eq!(blip_frequency('a', "neutral", 0.5), Some(blip_pitch("neutral")));
+        assert!(blip_pitch("distrust") < blip_pitch("neutral") && blip_pitch("neutral") < blip_pitch("trust"));
+
+        // La variación se queda en ±5 % aunque `jitter` se salga de rango
+        let base = blip_pitch("trust");
+        for jitter in [-1.0, 0.0, 1.0, 2.0] {
+            let pitch = blip_frequency('ñ', "trust", jitter).unwrap();
+            assert!((base * 0.95 - 0.01..=base * 1.05 + 0.01).contains(&pitch), "{}", pitch);
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! Lenguaje de condiciones para los diálogos.
+//!
+//! Ejemplo: `affection >= 40 && day > 3 && time == "night" && last_action == "talk"`.
+//! Admite `&&`, `||`, `!`, paréntesis y las comparaciones `== != < <= > >=`.
+
+use crate::engine::GameState;
+use serde::Deserialize;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Int,
+    Str,
+}
+
+/// Variables del estado de juego disponibles en las condiciones.
+const VARIABLES: [(&str, Type); 10] = [
+    ("affection", Type::Int),
+    ("trust", Type::Int),
+    ("day", Type::Int),
+    ("hour", Type::Int),
+    ("minute", Type::Int),
+    ("actions_taken", Type::Int),
+    ("daily_affection", Type::Int),
+    ("daily_trust", Type::Int),
+    ("time", Type::Str),
+    ("last_action", Type::Str),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Int(i64),
+    Str(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operand {
+    Var(&'static str),
+    Lit(Value),
+}
+
+impl Operand {
+    fn ty(&self) -> Type {
+        match self {
+            Operand::Var(name) => VARIABLES.iter().find(|(v, _)| v == name).map(|(_, t)| *t).unwrap_or(Type::Int),
+            Operand::Lit(Value::Int(_)) => Type::Int,
+            Operand::Lit(Value::Str(_)) => Type::Str,
+        }
+    }
+
+    fn resolve(&self, state: &GameState) -> Value {
+        match self {
+            Operand::Lit(v) => v.clone(),
+            Operand::Var(name) => match *name {
+                "affection" => Value::Int(state.affection as i64),
+                "trust" => Value::Int(state.trust as i64),
+                "day" => Value::Int(state.day as i64),
+                "hour" => Value::Int(state.hour as i64),
+                "minute" => Value::Int(state.minute as i64),
+                "actions_taken" => Value::Int(state.actions_taken as i64),
+                "daily_affection" => Value::Int(state.daily_affection as i64),
+                "daily_trust" => Value::Int(state.daily_trust as i64),
+                "time" => Value::Str(state.time_of_day.clone()),
+                "last_action" => Value::Str(state.last_action.clone()),
+                _ => Value::Int(0),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Cmp(Operand, CmpOp, Operand),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, state: &GameState) -> bool {
+        match self {
+            Expr::Not(e) => !e.eval(state),
+            Expr::And(a, b) => a.eval(state) && b.eval(state),
+            Expr::Or(a, b) => a.eval(state) || b.eval(state),
+            Expr::Cmp(l, op, r) => {
+                let ordering = match (l.resolve(state), r.resolve(state)) {
+                    (Value::Int(a), Value::Int(b)) => a.cmp(&b),
+                    (Value::Str(a), Value::Str(b)) => a.cmp(&b),
+                    _ => return false,
+                };
+                match op {
+                    CmpOp::Eq => ordering.is_eq(),
+                    CmpOp::Ne => ordering.is_ne(),
+                    CmpOp::Lt => ordering.is_lt(),
+                    CmpOp::Le => ordering.is_le(),
+                    CmpOp::Gt => ordering.is_gt(),
+                    CmpOp::Ge => ordering.is_ge(),
+                }
+            }
+        }
+    }
+}
+
+/// Condición ya analizada, lista para evaluarse contra el estado.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Condition {
+    expr: Expr,
+}
+
+impl Condition {
+    pub fn parse(source: &str) -> Result<Self, ConditionError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0, depth: 0 };
+        let expr = parser.or()?;
+        if let Some(tok) = parser.tokens.get(parser.pos) {
+            return Err(ConditionError(format!("símbolo inesperado '{}'", tok)));
+        }
+        Ok(Self { expr })
+    }
+
+    pub fn evaluate(&self, state: &GameState) -> bool {
+        self.expr.eval(state)
+    }
+}
+
+impl TryFrom<String> for Condition {
+    type Error = ConditionError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::parse(&source)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionError(pub String);
+
+impl fmt::Display for ConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "condición inválida: {}", self.0)
+    }
+}
+
+impl std::error::Error for ConditionError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Int(i64),
+    Str(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Int(n) => write!(f, "{}", n),
+            Token::Str(s) => write!(f, "\"{}\"", s),
+            Token::Op(op) => write!(f, "{}", op),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+        }
+    }
+}
+
+const OPERATORS: [&str; 9] = ["&&", "||", "==", "!=", "<=", ">=", "<", ">", "!"];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ConditionError> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = source.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if c == '"' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&ch| ch == '"')
+                .ok_or_else(|| ConditionError("comillas sin cerrar".to_string()))?;
+            tokens.push(Token::Str(chars[i + 1..i + 1 + end].iter().collect()));
+            i += end + 2;
+        } else if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text.parse().map_err(|_| ConditionError(format!("número inválido '{}'", text)))?;
+            tokens.push(Token::Int(n));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..].iter().take(2).collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .ok_or_else(|| ConditionError(format!("carácter inesperado '{}'", c)))?;
+            tokens.push(Token::Op(op));
+            i += op.len();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Anidamiento máximo de paréntesis y `!`. El analizador es recursivo: sin límite,
+/// una condición con miles de `(` desbordaría la pila al cargar los datos.
+const MAX_DEPTH: usize = 64;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, ConditionError> {
+        let mut expr = self.and()?;
+        while self.eat_op("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ConditionError> {
+        let mut expr = self.unary()?;
+        while self.eat_op("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ConditionError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(ConditionError(format!("demasiado anidada (máx. {} niveles)", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let expr = self.nested();
+        self.depth -= 1;
+        expr
+    }
+
+    fn nested(&mut self) -> Result<Expr, ConditionError> {
+        if self.eat_op("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.or()?;
+            if self.peek() != Some(&Token::RParen) {
+                return Err(ConditionError("falta ')'".to_string()));
+            }
+            self.pos += 1;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ConditionError> {
+        let left = self.operand()?;
+        let op = match self.peek() {
+            Some(Token::Op("==")) => CmpOp::Eq,
+            Some(Token::Op("!=")) => CmpOp::Ne,
+            Some(Token::Op("<")) => CmpOp::Lt,
+            Some(Token::Op("<=")) => CmpOp::Le,
+            Some(Token::Op(">")) => CmpOp::Gt,
+            Some(Token::Op(">=")) => CmpOp::Ge,
+            Some(tok) => return Err(ConditionError(format!("se esperaba una comparación y se encontró '{}'", tok))),
+            None => return Err(ConditionError("se esperaba una comparación".to_string())),
+        };
+        self.pos += 1;
+        let right = self.operand()?;
+
+        if left.ty() != right.ty() {
+            return Err(ConditionError("no se pueden comparar textos con números".to_string()));
+        }
+        if left.ty() == Type::Str && !matches!(op, CmpOp::Eq | CmpOp::Ne) {
+            return Err(ConditionError("los textos sólo admiten '==' y '!='".to_string()));
+        }
+        Ok(Expr::Cmp(left, op, right))
+    }
+
+    fn operand(&mut self) -> Result<Operand, ConditionError> {
+        let tok = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| ConditionError("expresión incompleta".to_string()))?;
+        self.pos += 1;
+        match tok {
+            Token::Int(n) => Ok(Operand::Lit(Value::Int(n))),
+            Token::Str(s) => Ok(Operand::Lit(Value::Str(s))),
+            Token::Ident(name) => VARIABLES
+                .iter()
+                .find(|(v, _)| *v == name)
+                .map(|(v, _)| Operand::Var(v))
+                .ok_or_else(|| ConditionError(format!("variable desconocida '{}'", name))),
+            other => Err(ConditionError(format!("símbolo inesperado '{}'", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_conditions() {
+        let cond = Condition::parse(r#"affection >= 40 && day > 3 && time == "night" && last_action == "talk""#).unwrap();
+        let mut state = GameState {
+            affection: 45,
+            day: 4,
+            time_of_day: "night".to_string(),
+            last_action: "talk".to_string(),
+            ..Default::default()
+        };
+        assert!(cond.evaluate(&state));
+
+        state.day = 3;
+        assert!(!cond.evaluate(&state));
+
+        let cond = Condition::parse(r#"!(trust < 10 || time == "morning")"#).unwrap();
+        assert!(!cond.evaluate(&state));
+        state.trust = 12;
+        assert!(cond.evaluate(&state));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Condition::parse("affection >=").is_err());
+        assert!(Condition::parse("afection > 3").unwrap_err().0.contains("afection"));
+        assert!(Condition::parse(r#"time > "night""#).is_err());
+        assert!(Condition::parse(r#"day == "3""#).is_err());
+        assert!(Condition::parse("(day > 3").is_err());
+        assert!(Condition::parse("day > 3 trust").is_err());
+
+        let deep = format!("{}day > 3{}", "(".repeat(10_000), ")".repeat(10_000));
+        assert!(Condition::parse(&deep).unwrap_err().0.contains("anidada"));
+        assert!(Condition::parse(&format!("{}day > 3", "!".repeat(10_000))).is_err());
+        assert!(Condition::parse(&format!("{}day > 3{}", "(".repeat(20), ")".repeat(20))).is_ok());
+    }
+}
@@ -0,0 +1,227 @@
+//! Preferencias del jugador en `config.toml` (ver `paths.rs` para su ubicación).
+//!
+//! Cualquier clave ausente toma su valor por defecto, así que un archivo vacío
+//! o inexistente equivale a la configuración inicial.
+
+use crate::release::{self, Channel, SourceConfig};
+use crate::typewriter::TextSpeed;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub display: DisplayConfig,
+    pub audio: AudioConfig,
+    pub text: TextConfig,
+    pub updates: UpdateConfig,
+    pub accessibility: AccessibilityConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    /// Muestra el retrato de Sylvie.
+    pub show_portrait: bool,
+    /// Usa colores; si no, todo se dibuja con los colores del terminal.
+    pub colors: bool,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self { show_portrait: true, colors: true }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct AudioConfig {
+    /// Entre 0.0 y 1.0.
+    pub volume: f32,
+    pub muted: bool,
+    /// Pitidos de voz al revelar el texto.
+    pub voice: bool,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self { volume: 0.7, muted: false, voice: true }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TextConfig {
+    pub speed: TextSpeed,
+    /// Segundos que el diálogo completo queda en pantalla antes de borrarse; 0 lo desactiva.
+    pub auto_advance: u64,
+}
+
+impl Default for TextConfig {
+    fn default() -> Self {
+        Self { speed: TextSpeed::Normal, auto_advance: 0 }
+    }
+}
+
+/// Intervalo máximo entre búsquedas de versiones: un año.
+pub const MAX_INTERVAL_HOURS: u64 = 24 * 366;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpdateConfig {
+    /// Busca una versión nueva al arrancar (en segundo plano).
+    pub check_on_startup: bool,
+    /// Horas mínimas entre dos búsquedas; 0 busca en cada arranque.
+    /// Como mucho `MAX_INTERVAL_HOURS`.
+    pub interval_hours: u64,
+    /// De dónde se descargan las versiones.
+    pub source: SourceConfig,
+    pub channel: Channel,
+    /// Versión fijada: el actualizador no ofrece ninguna otra.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pin: Option<String>,
+    /// Versiones que no se ofrecen nunca.
+    pub skip: Vec<String>,
+    /// Rechaza las versiones que no publiquen `SHA256SUMS`. Desactivado por defecto
+    /// porque las versiones antiguas no lo incluyen; instalarlas sin sumas avisa.
+    pub require_checksums: bool,
+    /// Clave pública Ed25519 (hexadecimal). Si está, `SHA256SUMS` debe venir firmado.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            check_on_startup: true,
+            interval_hours: 24,
+            source: SourceConfig::default(),
+            channel: Channel::Stable,
+            pin: None,
+            skip: Vec::new(),
+            require_checksums: false,
+            public_key: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccessibilityConfig {
+    /// Colores de alto contraste (tiene prioridad sobre `display.colors`).
+    pub high_contrast: bool,
+    /// Muestra el texto de golpe, sin animación.
+    pub reduce_motion: bool,
+}
+
+/// La configuración del archivo y la que rige esta sesión, que además lleva las
+/// opciones de la línea de órdenes. Éstas no se guardan en `config.toml`.
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    pub saved: Config,
+    pub effective: Config,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, String),
+    Invalid(PathBuf, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            ConfigError::Parse(path, msg) => write!(f, "{}: {}", path.display(), msg.trim_end()),
+            ConfigError::Invalid(path, msg) => write!(f, "{}: {}", path.display(), msg),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Lee la configuración; si el archivo no existe, devuelve la de por defecto.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(ConfigError::Io(path.to_path_buf(), e)),
+        };
+        Self::parse(&content, path)
+    }
+
+    pub fn parse(content: &str, path: &Path) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(content).map_err(|e| ConfigError::Parse(path.to_path_buf(), e.to_string()))?;
+        if !(0.0..=1.0).contains(&config.audio.volume) {
+            return Err(ConfigError::Invalid(path.to_path_buf(), "audio.volume debe estar entre 0.0 y 1.0".to_string()));
+        }
+        if config.updates.interval_hours > MAX_INTERVAL_HOURS {
+            let msg = format!("updates.interval_hours no puede pasar de {} (un año)", MAX_INTERVAL_HOURS);
+            return Err(ConfigError::Invalid(path.to_path_buf(), msg));
+        }
+        let mut versions = config.updates.pin.iter().chain(&config.updates.skip);
+        if let Some(bad) = versions.find(|v| release::parse_version(v).is_none()) {
+            return Err(ConfigError::Invalid(path.to_path_buf(), format!("\"{}\" no es una versión válida (p. ej. 1.2.0)", bad)));
+        }
+        Ok(config)
+    }
+
+    /// Guarda de forma atómica: archivo temporal y renombrado.
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        let io_err = |e| ConfigError::Io(path.to_path_buf(), e);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(io_err)?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| ConfigError::Invalid(path.to_path_buf(), e.to_string()))?;
+        let tmp = path.with_extension("toml.tmp");
+        let mut file = std::fs::File::create(&tmp).map_err(io_err)?;
+        file.write_all(content.as_bytes()).map_err(io_err)?;
+        file.sync_all().map_err(io_err)?;
+        std::fs::rename(&tmp, path).map_err(io_err)
+    }
+
+    /// Velocidad efectiva, teniendo en cuenta las opciones de accesibilidad.
+    pub fn text_speed(&self) -> TextSpeed {
+        if self.accessibility.reduce_motion { TextSpeed::Instant } else { self.text.speed }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_file_and_round_trip() {
+        let config = Config::parse("[audio]\nmuted = true\n", Path::new("config.toml")).unwrap();
+        assert!(config.audio.muted);
+        assert_eq!(config.audio.volume, 0.7);
+        assert!(config.display.show_portrait);
+
+        let config = Config::parse("[updates.source]\nkind = \"local\"\npath = \"/srv/releases\"\n", Path::new("config.toml")).unwrap();
+        assert_eq!(config.updates.source, SourceConfig::Local { path: PathBuf::from("/srv/releases") });
+        assert!(Config::parse("[updates.source]\nkind = \"http\"\npath = \"/srv\"\n", Path::new("config.toml")).is_err());
+        let config = Config::parse("[updates]\nchannel = \"beta\"\nskip = [\"v1.2.0\"]\n", Path::new("config.toml")).unwrap();
+        assert_eq!(config.updates.channel, Channel::Beta);
+        assert!(Config::parse("[updates]\npin = \"pronto\"\n", Path::new("config.toml")).is_err());
+        assert!(Config::parse("[updates]\ninterval_hours = 9223372036854775807\n", Path::new("config.toml")).is_err());
+
+        let err = Config::parse("[audio]\nvolumen = 1\n", Path::new("config.toml")).unwrap_err();
+        assert!(err.to_string().starts_with("config.toml: "));
+
+        let path = std::env::temp_dir().join("tfeeling_test_config").join("config.toml");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+        let config = Config {
+            text: TextConfig { speed: TextSpeed::Fast, ..Default::default() },
+            accessibility: AccessibilityConfig { high_contrast: true, ..Default::default() },
+            ..Default::default()
+        };
+        config.save(&path).unwrap();
+        assert_eq!(Config::load(&path).unwrap(), config);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::condition::Condition;
+use crate::dialogue::{parse_json, DialogueLoadError};
+use crate::engine::GameState;
+use rand::seq::IndexedRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Respuesta que el jugador puede elegir en un nodo.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Choice {
+    pub label: String,
+    #[serde(default)]
+    pub affection: i32,
+    #[serde(default)]
+    pub trust: i32,
+    /// Nodo siguiente; si falta, la conversación termina.
+    #[serde(default)]
+    pub next: Option<String>,
+    #[serde(default)]
+    pub when: Option<Condition>,
+}
+
+/// Un nodo: lo que dice Sylvie y las respuestas posibles.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Node {
+    pub text: String,
+    #[serde(default)]
+    pub choices: Vec<Choice>,
+}
+
+impl Node {
+    pub fn available_choices(&self, state: &GameState) -> Vec<&Choice> {
+        self.choices
+            .iter()
+            .filter(|c| c.when.as_ref().is_none_or(|w| w.evaluate(state)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Conversation {
+    pub id: String,
+    #[serde(default)]
+    pub when: Option<Condition>,
+    pub start: String,
+    pub nodes: BTreeMap<String, Node>,
+}
+
+/// Posición actual dentro de una conversación, guardada en la partida.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConversationCursor {
+    pub conversation: String,
+    pub node: String,
+}
+
+/// Contenido de `modules/conversations.<idioma>.json`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConversationDb {
+    #[serde(default)]
+    pub conversations: Vec<Conversation>,
+}
+
+impl ConversationDb {
+    pub fn parse(content: &str, path: &Path) -> Result<Self, DialogueLoadError> {
+        let db: Self = parse_json(content, path)?;
+        db.validate(path)?;
+        Ok(db)
+    }
+
+    /// Comprueba que los nodos referenciados existan.
+    fn validate(&self, path: &Path) -> Result<(), DialogueLoadError> {
+        for (i, conv) in self.conversations.iter().enumerate() {
+            if self.conversations[..i].iter().any(|c| c.id == conv.id) {
+                return Err(DialogueLoadError::new(path, &format!("conversations[{}].id", i), format!("conversación duplicada '{}'", conv.id)));
+            }
+            if !conv.nodes.contains_key(&conv.start) {
+                return Err(DialogueLoadError::new(path, &format!("conversations[{}].start", i), format!("nodo inexistente '{}'", conv.start)));
+            }
+            for (name, node) in &conv.nodes {
+                for (j, choice) in node.choices.iter().enumerate() {
+                    if let Some(next) = &choice.next
+                        && !conv.nodes.contains_key(next)
+                    {
+                        let key = format!("conversations[{}].nodes.{}.choices[{}].next", i, name, j);
+                        return Err(DialogueLoadError::new(path, &key, format!("nodo inexistente '{}'", next)));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Elige al azar una conversación cuya condición se cumpla.
+    pub fn pick(&self, state: &GameState) -> Option<&Conversation> {
+        let available: Vec<&Conversation> = self
+            .conversations
+            .iter()
+            .filter(|c| c.when.as_ref().is_none_or(|w| w.evaluate(state)))
+            .collect();
+        let mut rng = rand::rng();
+        available.choose(&mut rng).copied()
+    }
+
+    pub fn node(&self, cursor: &ConversationCursor) -> Option<&Node> {
+        self.conversations
+            .iter()
+            .find(|c| c.id == cursor.conversation)
+            .and_then(|c| c.nodes.get(&cursor.node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_conversations_are_valid() {
+        let db = ConversationDb::parse(include_str!("../modules/conversations.es.json"), Path::new("conversations.es.json")).unwrap();
+        assert!(!db.conversations.is_empty());
+    }
+
+    #[test]
+    fn test_missing_node_is_reported() {
+        let content = r#"{ "conversations": [{
+            "id": "c", "start": "a",
+            "nodes": { "a": { "text": "hola", "choices": [{ "label": "?", "next": "b" }] } }
+        }] }"#;
+        let err = ConversationDb::parse(content, Path::new("conversations.json")).unwrap_err();
+        assert_eq!(err.key, "conversations[0].nodes.a.choices[0].next");
+    }
+}
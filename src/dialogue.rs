@@ -0,0 +1,354 @@
+use crate::condition::Condition;
+use crate::engine::GameState;
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+fn default_weight() -> u32 { 1 }
+
+fn default_repeat_window() -> usize { 5 }
+
+/// Una línea de diálogo. En el JSON puede ser un texto simple o un objeto
+/// `{ "id": "...", "text": "...", "when": "affection >= 40", "weight": 3, "once": true }`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogueLine {
+    /// Identificador estable de la línea, igual en todos los idiomas. Si el JSON
+    /// no da un `id`, es su posición en el archivo (p. ej. `actions.talk.low[3]`);
+    /// las líneas `once` deben darlo, porque la posición cambia al editar el archivo.
+    pub id: String,
+    pub text: String,
+    pub when: Option<Condition>,
+    /// Peso relativo en la selección aleatoria (por defecto 1).
+    pub weight: u32,
+    /// Si es `true`, la línea sólo se dice una vez por partida.
+    pub once: bool,
+}
+
+impl DialogueLine {
+    pub fn is_available(&self, state: &GameState) -> bool {
+        self.when.as_ref().is_none_or(|c| c.evaluate(state))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LineSpec {
+    #[serde(default)]
+    id: String,
+    text: String,
+    #[serde(default)]
+    when: Option<Condition>,
+    #[serde(default = "default_weight")]
+    weight: u32,
+    #[serde(default)]
+    once: bool,
+}
+
+impl<'de> Deserialize<'de> for DialogueLine {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LineVisitor;
+
+        impl<'de> Visitor<'de> for LineVisitor {
+            type Value = DialogueLine;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("un texto o un objeto con 'text' y 'when'")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<DialogueLine, E> {
+                Ok(DialogueLine { id: String::new(), text: v.to_string(), when: None, weight: 1, once: false })
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<DialogueLine, A::Error> {
+                let spec = LineSpec::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                if spec.once && spec.id.is_empty() {
+                    return Err(de::Error::custom("las líneas con 'once' necesitan un 'id'"));
+                }
+                Ok(DialogueLine { id: spec.id, text: spec.text, when: spec.when, weight: spec.weight, once: spec.once })
+            }
+        }
+
+        deserializer.deserialize_any(LineVisitor)
+    }
+}
+
+/// Diálogos de una acción, separados por nivel de afecto.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActionDialogues {
+    #[serde(default)]
+    pub low: Vec<DialogueLine>,
+    #[serde(default)]
+    pub high: Vec<DialogueLine>,
+}
+
+impl ActionDialogues {
+    pub fn tier(&self, high: bool) -> &[DialogueLine] {
+        if high { &self.high } else { &self.low }
+    }
+}
+
+/// Saludos y comentarios para cada fase del día.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimeDialogues {
+    #[serde(default)]
+    pub morning: Vec<DialogueLine>,
+    #[serde(default)]
+    pub afternoon: Vec<DialogueLine>,
+    #[serde(default)]
+    pub night: Vec<DialogueLine>,
+}
+
+impl TimeDialogues {
+    pub fn phase(&self, time_of_day: &str) -> &[DialogueLine] {
+        match time_of_day {
+            "morning" => &self.morning,
+            "afternoon" => &self.afternoon,
+            "night" => &self.night,
+            _ => &[],
+        }
+    }
+}
+
+/// Contenido de `modules/dialogues.<idioma>.json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DialogueDb {
+    #[serde(default)]
+    pub distrust: Vec<DialogueLine>,
+    #[serde(default)]
+    pub neutral: Vec<DialogueLine>,
+    #[serde(default)]
+    pub trust: Vec<DialogueLine>,
+    #[serde(default)]
+    pub actions: BTreeMap<String, ActionDialogues>,
+    #[serde(default)]
+    pub time: TimeDialogues,
+    /// Líneas que dependen sólo de su condición `when`, no de un grupo.
+    #[serde(default)]
+    pub conditional: Vec<DialogueLine>,
+    /// Número de líneas recientes que no se repiten.
+    #[serde(default = "default_repeat_window")]
+    pub repeat_window: usize,
+}
+
+impl Default for DialogueDb {
+    fn default() -> Self {
+        Self {
+            distrust: Vec::new(),
+            neutral: Vec::new(),
+            trust: Vec::new(),
+            actions: BTreeMap::new(),
+            time: TimeDialogues::default(),
+            conditional: Vec::new(),
+            repeat_window: default_repeat_window(),
+        }
+    }
+}
+
+impl DialogueDb {
+    pub fn parse(content: &str, path: &Path) -> Result<Self, DialogueLoadError> {
+        let mut db: Self = parse_json(content, path)?;
+        db.assign_ids();
+        let mut seen = HashSet::new();
+        for (key, lines) in db.buckets() {
+            if let Some((i, line)) = lines.iter().enumerate().find(|(_, l)| !seen.insert(l.id.as_str())) {
+                return Err(DialogueLoadError::new(path, &format!("{}[{}].id", key, i), format!("id repetido '{}'", line.id)));
+            }
+        }
+        Ok(db)
+    }
+
+    /// Da a cada línea sin `id` explícito el de su posición (`grupo[índice]`).
+    fn assign_ids(&mut self) {
+        let assign = |key: &str, lines: &mut [DialogueLine]| {
+            for (i, line) in lines.iter_mut().enumerate() {
+                if line.id.is_empty() {
+                    line.id = format!("{}[{}]", key, i);
+                }
+            }
+        };
+        assign("distrust", &mut self.distrust);
+        assign("neutral", &mut self.neutral);
+        assign("trust", &mut self.trust);
+        for (key, action) in &mut self.actions {
+            assign(&format!("actions.{}.low", key), &mut action.low);
+            assign(&format!("actions.{}.high", key), &mut action.high);
+        }
+        assign("time.morning", &mut self.time.morning);
+        assign("time.afternoon", &mut self.time.afternoon);
+        assign("time.night", &mut self.time.night);
+        assign("conditional", &mut self.conditional);
+    }
+
+    /// Completa una traducción con `base`: los grupos que falten o estén vacíos aquí
+    /// se toman de `base`.
+    pub fn fill_from(&mut self, base: DialogueDb) {
+        let keep = |lines: &mut Vec<DialogueLine>, base: Vec<DialogueLine>| {
+            if lines.is_empty() {
+                *lines = base;
+            }
+        };
+        keep(&mut self.distrust, base.distrust);
+        keep(&mut self.neutral, base.neutral);
+        keep(&mut self.trust, base.trust);
+        keep(&mut self.conditional, base.conditional);
+        keep(&mut self.time.morning, base.time.morning);
+        keep(&mut self.time.afternoon, base.time.afternoon);
+        keep(&mut self.time.night, base.time.night);
+        for (key, base) in base.actions {
+            let action = self.actions.entry(key).or_default();
+            keep(&mut action.low, base.low);
+            keep(&mut action.high, base.high);
+        }
+    }
+
+    /// Todos los grupos de líneas con su clave (`distrust`, `time.night`,
+    /// `actions.talk.low`...), en el orden del archivo.
+    pub fn buckets(&self) -> Vec<(String, &[DialogueLine])> {
+        let mut buckets: Vec<(String, &[DialogueLine])> = vec![
+            ("distrust".to_string(), &self.distrust),
+            ("neutral".to_string(), &self.neutral),
+            ("trust".to_string(), &self.trust),
+        ];
+        for (key, action) in &self.actions {
+            buckets.push((format!("actions.{}.low", key), &action.low));
+            buckets.push((format!("actions.{}.high", key), &action.high));
+        }
+        buckets.push(("time.morning".to_string(), &self.time.morning));
+        buckets.push(("time.afternoon".to_string(), &self.time.afternoon));
+        buckets.push(("time.night".to_string(), &self.time.night));
+        buckets.push(("conditional".to_string(), &self.conditional));
+        buckets
+    }
+
+    /// Lista base según el nivel de afecto y confianza.
+    pub fn mood(&self, mood: &str) -> &[DialogueLine] {
+        match mood {
+            "distrust" => &self.distrust,
+            "neutral" => &self.neutral,
+            "trust" => &self.trust,
+            _ => &[],
+        }
+    }
+}
+
+/// Error al cargar un archivo de diálogos, con su ubicación.
+#[derive(Debug)]
+pub struct DialogueLoadError {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    /// Ruta de la clave problemática (p. ej. `actions.talk.low[3]`).
+    pub key: String,
+    pub message: String,
+}
+
+impl DialogueLoadError {
+    /// Error sin posición en el archivo (lectura o validación posterior).
+    pub fn new(path: &Path, key: &str, message: String) -> Self {
+        Self { path: path.to_path_buf(), line: 0, column: 0, key: key.to_string(), message }
+    }
+}
+
+/// Deserializa un archivo JSON de contenido indicando línea y clave en caso de error.
+pub fn parse_json<T: de::DeserializeOwned>(content: &str, path: &Path) -> Result<T, DialogueLoadError> {
+    let de = &mut serde_json::Deserializer::from_str(content);
+    serde_path_to_error::deserialize(de).map_err(|e| {
+        let key = e.path().to_string();
+        let inner = e.into_inner();
+        // serde_json ya añade la posición al mensaje; la mostramos aparte
+        let suffix = format!(" at line {} column {}", inner.line(), inner.column());
+        let message = inner.to_string();
+        DialogueLoadError {
+            path: path.to_path_buf(),
+            line: inner.line(),
+            column: inner.column(),
+            key: if key == "." { String::new() } else { key },
+            message: message.strip_suffix(&suffix).unwrap_or(&message).to_string(),
+        }
+    })
+}
+
+impl fmt::Display for DialogueLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if self.line > 0 {
+            write!(f, ":{}:{}", self.line, self.column)?;
+        }
+        if !self.key.is_empty() {
+            write!(f, " (clave '{}')", self.key)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for DialogueLoadError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_dialogues_parse() {
+        let db = DialogueDb::parse(include_str!("../modules/dialogues.es.json"), Path::new("dialogues.es.json")).unwrap();
+        assert!(!db.distrust.is_empty());
+        assert!(!db.time.phase("night").is_empty());
+        assert!(!db.actions["pat_head"].tier(true).is_empty());
+
+        let mut en = DialogueDb::parse(r#"{ "neutral": ["Hi"], "actions": { "pat_head": { "low": ["Hm?"] } } }"#, Path::new("dialogues.en.json")).unwrap();
+        en.fill_from(db.clone());
+        assert_eq!(en.neutral[0].text, "Hi");
+        assert_eq!(en.distrust, db.distrust);
+        assert_eq!(en.actions["pat_head"].low[0].text, "Hm?");
+        assert_eq!(en.actions["pat_head"].high, db.actions["pat_head"].high);
+        assert_eq!(en.actions["give_treat"].low, db.actions["give_treat"].low);
+    }
+
+    #[test]
+    fn test_line_ids() {
+        let db = DialogueDb::parse(r#"{
+                "neutral": ["a", { "id": "saludo", "text": "b" }],
+                "actions": { "talk": { "high": ["c"] } }
+            }"#, Path::new("test")).unwrap();
+        assert_eq!(db.neutral[0].id, "neutral[0]");
+        assert_eq!(db.neutral[1].id, "saludo");
+        assert_eq!(db.actions["talk"].high[0].id, "actions.talk.high[0]");
+
+        let once = DialogueDb::parse(r#"{ "trust": ["a", { "text": "b", "once": true }] }"#, Path::new("test"));
+        assert_eq!(once.unwrap_err().key, "trust[1]");
+        let repeated = DialogueDb::parse(r#"{ "trust": [{ "id": "x", "text": "a" }, { "id": "x", "text": "b" }] }"#, Path::new("test"));
+        assert_eq!(repeated.unwrap_err().key, "trust[1].id");
+    }
+
+    #[test]
+    fn test_load_error_reports_location() {
+        let content = "{\n  \"actions\": {\n    \"talk\": { \"low\": [\"hola\", 3] }\n  }\n}";
+        let err = DialogueDb::parse(content, Path::new("dialogues.json")).unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.key, "actions.talk.low[1]");
+        assert!(err.to_string().starts_with("dialogues.json:3:"));
+
+        let err = DialogueDb::parse(r#"{ "trsut": [] }"#, Path::new("dialogues.json")).unwrap_err();
+        assert!(err.message.contains("trsut"));
+
+        let err = DialogueDb::parse(r#"{ "conditional": [{ "text": "hola", "when": "day >" }] }"#, Path::new("dialogues.json")).unwrap_err();
+        assert_eq!(err.key, "conditional[0].when");
+        assert!(err.message.contains("condición inválida"));
+    }
+
+    #[test]
+    fn test_conditional_lines() {
+        let db = DialogueDb::parse(r#"{
+            "neutral": ["plain", { "text": "late", "when": "hour >= 20" }]
+        }"#, Path::new("dialogues.json")).unwrap();
+        let mut state = GameState::default();
+        assert!(db.neutral[0].is_available(&state));
+        assert!(!db.neutral[1].is_available(&state));
+        state.hour = 21;
+        assert!(db.neutral[1].is_available(&state));
+    }
+}
@@ -1,10 +1,18 @@
+use crate::action::{ActionDef, ActionError, ActionRegistry};
+use crate::assets;
+use crate::conversation::{Choice, ConversationCursor, ConversationDb};
+use crate::dialogue::{DialogueDb, DialogueLine};
+use crate::i18n::{self, tr};
+use crate::paths::Paths;
+use crate::save::{self, SaveError};
+use crate::sprite::SpriteManifest;
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use rand::seq::IndexedRandom;
 
-fn default_day() -> u32 { 1 }
-
+/// Estado de la partida. Al añadir o renombrar campos, sube `save::SAVE_VERSION`
+/// y añade la migración correspondiente en `save.rs`.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GameState {
     pub affection: i32,
@@ -14,12 +22,39 @@ pub struct GameState {
     pub time_of_day: String,
     pub hour: u32,
     pub minute: u32,
-    #[serde(default = "default_day")]
     pub day: u32,
     pub actions_taken: u32,
     pub daily_affection: i32,
     pub daily_trust: i32,
     pub last_dialogue: String,
+    /// Minuto de juego (ver `clock_minutes`) en que se usó cada acción por última vez.
+    pub cooldowns: HashMap<String, u32>,
+    /// Ids de las últimas líneas dichas, para no repetirlas dentro de la ventana.
+    pub recent_dialogues: VecDeque<String>,
+    /// Ids de las líneas marcadas como `once` que ya se han dicho.
+    pub used_once: BTreeSet<String>,
+    /// Conversación en curso, si Sylvie espera una respuesta.
+    pub conversation: Option<ConversationCursor>,
+    /// Últimas `LOG_LEN` acciones y respuestas, la más reciente al final.
+    pub log: VecDeque<LogEntry>,
+}
+
+/// Entradas que se conservan en `GameState::log`.
+pub const LOG_LEN: usize = 50;
+
+/// Una acción (o respuesta) del jugador y lo que contestó Sylvie.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    /// Id de la acción, o `reply` para las respuestas de una conversación.
+    pub action: String,
+    /// Etiqueta del menú o de la respuesta elegida.
+    pub label: String,
+    pub affection: i32,
+    pub trust: i32,
+    pub dialogue: String,
 }
 
 impl Default for GameState {
@@ -37,106 +72,226 @@ impl Default for GameState {
             daily_affection: 0,
             daily_trust: 0,
             last_dialogue: "...".to_string(),
+            cooldowns: HashMap::new(),
+            recent_dialogues: VecDeque::new(),
+            used_once: BTreeSet::new(),
+            conversation: None,
+            log: VecDeque::new(),
         }
     }
 }
 
+impl GameState {
+    /// Minutos transcurridos desde el inicio de la partida. Una partida editada a
+    /// mano con `day: 0` cuenta como el primer día.
+    pub fn clock_minutes(&self) -> u32 {
+        self.day.saturating_sub(1) * 24 * 60 + self.hour * 60 + self.minute
+    }
+}
+
 pub struct Engine {
     pub state: GameState,
-    pub dialogues: serde_json::Value,
-    save_path: PathBuf,
+    pub dialogues: DialogueDb,
+    pub conversations: ConversationDb,
+    pub actions: ActionRegistry,
+    pub sprites: SpriteManifest,
+    save_path: Option<PathBuf>,
+}
+
+/// Versión en el idioma por defecto de `name`, que siempre debe existir.
+fn localized_default(paths: &Paths, name: &str) -> Result<assets::Asset, Box<dyn std::error::Error>> {
+    assets::load_localized(paths, name, i18n::DEFAULT_LOCALE)?
+        .ok_or_else(|| tr!("assets.not_found", path = assets::localized_name(name, i18n::DEFAULT_LOCALE)).into())
+}
+
+/// Diálogos en `locale`, completados con los del idioma por defecto.
+fn load_dialogues(paths: &Paths, locale: &str) -> Result<DialogueDb, Box<dyn std::error::Error>> {
+    let base = localized_default(paths, "dialogues.json")?;
+    let base = DialogueDb::parse(base.text()?, &base.origin)?;
+    if locale == i18n::DEFAULT_LOCALE {
+        return Ok(base);
+    }
+    let Some(asset) = assets::load_localized(paths, "dialogues.json", locale)? else { return Ok(base) };
+    let mut db = DialogueDb::parse(asset.text()?, &asset.origin)?;
+    db.fill_from(base);
+    Ok(db)
 }
 
 impl Engine {
-    pub fn new() -> Self {
-        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-        let save_path = PathBuf::from(home).join(".sylvie_save.json");
-        
-        let mut engine = Self {
-            state: GameState::default(),
-            dialogues: serde_json::Value::Null,
-            save_path,
+    /// Carga los datos del juego; la partida se abre después con `open_save`.
+    pub fn new(paths: &Paths) -> Result<Self, Box<dyn std::error::Error>> {
+        let locale = i18n::locale();
+        let conversations = match assets::load_localized(paths, "conversations.json", locale)? {
+            Some(asset) => asset,
+            None => localized_default(paths, "conversations.json")?,
         };
-        
-        engine.load_state();
-        engine.load_dialogues();
-        engine
+        let actions = assets::load(paths, "actions.json")?;
+        let sprites = assets::load(paths, "art/sprites.json")?;
+
+        let mut actions = ActionRegistry::parse(actions.text()?).map_err(|e| format!("{}: {}", actions.origin.display(), e))?;
+        actions.localize();
+
+        Ok(Self {
+            state: GameState::default(),
+            dialogues: load_dialogues(paths, locale)?,
+            conversations: ConversationDb::parse(conversations.text()?, &conversations.origin)?,
+            actions,
+            sprites: SpriteManifest::parse(sprites.text()?, &sprites.origin)?,
+            save_path: None,
+        })
     }
 
-    fn load_state(&mut self) {
-        if let Ok(content) = fs::read_to_string(&self.save_path) {
-            if let Ok(state) = serde_json::from_str(&content) {
-                self.state = state;
-            }
+    /// Abre la partida guardada en `path` (o una nueva si no existe).
+    /// Devuelve la copia de seguridad usada si el archivo estaba dañado.
+    pub fn open_save(&mut self, path: PathBuf) -> Result<Option<PathBuf>, SaveError> {
+        let mut recovered_from = None;
+        self.state = if path.exists() {
+            let loaded = save::load_with_recovery(&path)?;
+            recovered_from = loaded.recovered_from;
+            loaded.state
+        } else {
+            GameState::default()
+        };
+        self.save_path = Some(path);
+
+        // Si el contenido cambió y el nodo ya no existe o no le quedan respuestas,
+        // se cierra la conversación
+        if self.state.conversation.is_some() && self.replies().is_empty() {
+            self.state.conversation = None;
         }
+        Ok(recovered_from)
     }
 
-    fn load_dialogues(&mut self) {
-        let path = PathBuf::from("modules/dialogues.json");
-        if let Ok(content) = fs::read_to_string(path) {
-            if let Ok(dialogues) = serde_json::from_str(&content) {
-                self.dialogues = dialogues;
-            }
-        }
+    /// Cierra la partida actual sin guardarla (p. ej. si se ha borrado).
+    pub fn close_save(&mut self) {
+        self.save_path = None;
+        self.state = GameState::default();
     }
 
-    pub fn save_state(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let content = serde_json::to_string_pretty(&self.state)?;
-        fs::write(&self.save_path, content)?;
-        Ok(())
+    pub fn save_path(&self) -> Option<&Path> {
+        self.save_path.as_deref()
     }
 
-    pub fn interact(&mut self, action: &str) {
-        let old_phase = self.state.time_of_day.clone();
+    pub fn save_state(&self) -> Result<(), SaveError> {
+        match &self.save_path {
+            Some(path) => save::write_state(path, &self.state),
+            None => Ok(()),
+        }
+    }
 
-        match action {
-            "pat_head" => {
-                if self.state.daily_affection < 10 {
-                    self.state.affection += 2;
-                    self.state.daily_affection += 2;
-                }
-                if self.state.daily_trust < 5 {
-                    self.state.trust += 1;
-                    self.state.daily_trust += 1;
-                }
-            }
-            "give_treat" => {
-                if self.state.daily_affection < 10 {
-                    let gain = (10 - self.state.daily_affection).min(5);
-                    self.state.affection += gain;
-                    self.state.daily_affection += gain;
-                }
-                if self.state.daily_trust < 5 {
-                    let gain = (5 - self.state.daily_trust).min(2);
-                    self.state.trust += gain;
-                    self.state.daily_trust += gain;
-                }
-            }
-            "talk" => {
-                // Hablar ahora da un poco de afecto/confianza pero muy poco
-                if self.state.daily_affection < 10 {
-                    self.state.affection += 1;
-                    self.state.daily_affection += 1;
-                }
-            }
-            _ => {}
+    pub fn interact(&mut self, action_id: &str) -> Result<(), ActionError> {
+        if self.state.conversation.is_some() {
+            return Err(ActionError::InConversation);
         }
+        let action = self.actions.get(action_id)?.clone();
+        action.check(&self.state)?;
+        let old_phase = self.state.time_of_day.clone();
+        let before = self.state.clone();
+
+        self.apply_gains(action.affection, action.trust);
 
-        self.state.last_action = action.to_string();
+        self.state.cooldowns.insert(action.id.clone(), self.state.clock_minutes());
+        self.state.last_action = action.id.clone();
         self.state.actions_taken += 1;
-        self.state.last_dialogue = self.get_random_dialogue(Some(action));
+        let line = self.get_random_dialogue(Some(&action));
+
+        self.advance_time(action.minutes);
+
+        let conversation = if action.opens_conversation {
+            self.conversations.pick(&self.state).map(|c| (c.id.clone(), c.start.clone()))
+        } else {
+            None
+        };
 
-        // Cada acción consume tiempo equilibrado (2 horas)
-        let minutes = 120; 
-        
-        self.advance_time(minutes);
+        if let Some((conversation, node)) = conversation {
+            self.enter_node(ConversationCursor { conversation, node });
+        } else if self.state.time_of_day != old_phase {
+            // Si la fase cambió, Sylvie saluda
+            let greeting = self.get_time_greeting();
+            self.say(greeting);
+        } else {
+            self.say(line);
+        }
+
+        self.record(&before, &action.id, &action.label);
+        self.save_state().map_err(|e| ActionError::Save(e.to_string()))
+    }
 
-        // Si la fase cambió, Sylvie saluda
-        if self.state.time_of_day != old_phase {
-            self.state.last_dialogue = self.get_time_greeting();
+    /// Respuestas disponibles en el nodo actual de la conversación.
+    pub fn replies(&self) -> Vec<&Choice> {
+        self.state
+            .conversation
+            .as_ref()
+            .and_then(|cursor| self.conversations.node(cursor))
+            .map(|node| node.available_choices(&self.state))
+            .unwrap_or_default()
+    }
+
+    /// Elige la respuesta `index` de `replies()` y avanza la conversación.
+    pub fn reply(&mut self, index: usize) -> Result<(), ActionError> {
+        let cursor = self.state.conversation.clone().ok_or(ActionError::InvalidReply(index))?;
+        let choice = self.replies().get(index).map(|c| (*c).clone()).ok_or(ActionError::InvalidReply(index))?;
+        let before = self.state.clone();
+
+        self.apply_gains(choice.affection, choice.trust);
+        match choice.next {
+            Some(node) => self.enter_node(ConversationCursor { node, ..cursor }),
+            None => {
+                self.state.conversation = None;
+                self.state.last_dialogue = "...".to_string();
+            }
         }
 
-        let _ = self.save_state();
+        self.record(&before, "reply", &choice.label);
+        self.save_state().map_err(|e| ActionError::Save(e.to_string()))
+    }
+
+    /// Añade al registro lo ocurrido desde `before`.
+    fn record(&mut self, before: &GameState, action: &str, label: &str) {
+        self.state.log.push_back(LogEntry {
+            day: before.day,
+            hour: before.hour,
+            minute: before.minute,
+            action: action.to_string(),
+            label: label.to_string(),
+            affection: self.state.affection - before.affection,
+            trust: self.state.trust - before.trust,
+            dialogue: self.state.last_dialogue.clone(),
+        });
+        while self.state.log.len() > LOG_LEN {
+            self.state.log.pop_front();
+        }
+    }
+
+    /// Muestra el texto del nodo; si no le queda ninguna respuesta disponible (no
+    /// tiene o las ocultan sus condiciones), la conversación termina.
+    fn enter_node(&mut self, cursor: ConversationCursor) {
+        let Some(node) = self.conversations.node(&cursor) else {
+            self.state.conversation = None;
+            return;
+        };
+        self.state.last_dialogue = node.text.clone();
+        let open = !node.available_choices(&self.state).is_empty();
+        self.state.conversation = if open { Some(cursor) } else { None };
+    }
+
+    /// Suma las ganancias sin pasar de los límites diarios: si sólo falta una parte
+    /// para el límite, se gana esa parte (antes `pat_head` y `talk` podían pasarse).
+    /// Las pérdidas (valores negativos) se aplican siempre y no cuentan para el límite,
+    /// para que una respuesta brusca no dé margen extra ese día.
+    fn apply_gains(&mut self, affection: i32, trust: i32) {
+        fn apply(value: &mut i32, daily: &mut i32, cap: i32, amount: i32) {
+            if amount < 0 {
+                *value += amount;
+            } else if *daily < cap {
+                let gain = (cap - *daily).min(amount);
+                *value += gain;
+                *daily += gain;
+            }
+        }
+        let caps = self.actions.caps;
+        apply(&mut self.state.affection, &mut self.state.daily_affection, caps.affection, affection);
+        apply(&mut self.state.trust, &mut self.state.daily_trust, caps.trust, trust);
     }
 
     pub fn advance_time(&mut self, minutes: u32) {
@@ -170,74 +325,86 @@ impl Engine {
         };
     }
 
-    fn get_time_greeting(&self) -> String {
-        if let Some(time_node) = self.dialogues.get("time") {
-            if let Some(list) = time_node.get(&self.state.time_of_day).and_then(|v| v.as_array()) {
-                if !list.is_empty() {
-                    let mut rng = rand::rng();
-                    return list.choose(&mut rng).and_then(|v| v.as_str()).unwrap_or("...").to_string();
-                }
-            }
+    /// Muestra una línea y la registra en el historial de repeticiones.
+    fn say(&mut self, line: Option<DialogueLine>) {
+        let Some(line) = line else {
+            self.state.last_dialogue = "...".to_string();
+            return;
+        };
+        if line.once {
+            self.state.used_once.insert(line.id.clone());
+        }
+        self.state.recent_dialogues.push_back(line.id);
+        while self.state.recent_dialogues.len() > self.dialogues.repeat_window {
+            self.state.recent_dialogues.pop_front();
         }
-        "...".to_string()
+        self.state.last_dialogue = line.text;
     }
 
-    pub fn get_random_dialogue(&self, action_override: Option<&str>) -> String {
-        let mut choices = Vec::new();
+    /// Elige una línea por peso, evitando las recientes y las `once` ya usadas.
+    fn pick_line(&self, mut choices: Vec<&DialogueLine>) -> Option<DialogueLine> {
+        choices.retain(|l| {
+            l.weight > 0 && l.is_available(&self.state) && !(l.once && self.state.used_once.contains(&l.id))
+        });
+
+        let fresh: Vec<&DialogueLine> = choices
+            .iter()
+            .copied()
+            .filter(|l| !self.state.recent_dialogues.contains(&l.id))
+            .collect();
+        // Si todo se dijo hace poco, mejor repetir que quedarse callada
+        let pool = if fresh.is_empty() { choices } else { fresh };
+
+        let mut rng = rand::rng();
+        pool.choose_weighted(&mut rng, |l| l.weight).ok().map(|l| (*l).clone())
+    }
+
+    fn get_time_greeting(&self) -> Option<DialogueLine> {
+        self.pick_line(self.dialogues.time.phase(&self.state.time_of_day).iter().collect())
+    }
+
+    /// Ánimo de Sylvie según sus estadísticas: `distrust`, `neutral` o `trust`.
+    pub fn mood(&self) -> &'static str {
         let affection = self.state.affection;
         let trust = self.state.trust;
-
-        // 1. Base stats
         if affection < 20 || trust < 10 {
-            if let Some(list) = self.dialogues.get("distrust").and_then(|v| v.as_array()) {
-                choices.extend(list);
-            }
+            "distrust"
         } else if affection < 60 || trust < 30 {
-            if let Some(list) = self.dialogues.get("neutral").and_then(|v| v.as_array()) {
-                choices.extend(list);
-            }
+            "neutral"
         } else {
-            if let Some(list) = self.dialogues.get("trust").and_then(|v| v.as_array()) {
-                choices.extend(list);
-            }
+            "trust"
         }
+    }
 
-        // 2. Time of day
-        if let Some(time_node) = self.dialogues.get("time") {
-            if let Some(list) = time_node.get(&self.state.time_of_day).and_then(|v| v.as_array()) {
-                choices.extend(list);
-            }
-        }
+    pub fn get_random_dialogue(&self, action_override: Option<&ActionDef>) -> Option<DialogueLine> {
+        let mut choices: Vec<&DialogueLine> = Vec::new();
 
-        // 3. Action
-        let action = action_override.unwrap_or(&self.state.last_action);
-        if let Some(action_node) = self.dialogues.get("actions").and_then(|v| v.get(action)) {
-            let tier = if affection >= 40 { "high" } else { "low" };
-            if let Some(list) = action_node.get(tier).and_then(|v| v.as_array()) {
-                choices.extend(list);
-            }
-        }
+        // 1. Base stats
+        choices.extend(self.dialogues.mood(self.mood()));
 
-        // 4. Special for treats
-        if action_override == Some("give_treat") {
-            if let Some(action_node) = self.dialogues.get("actions").and_then(|v| v.get("give_treat")) {
-                let tier = if affection >= 40 { "high" } else { "low" };
-                if let Some(list) = action_node.get(tier).and_then(|v| v.as_array()) {
-                    // Override choices with just treat dialogues
-                    if !list.is_empty() {
-                        let mut rng = rand::rng();
-                        return list.choose(&mut rng).and_then(|v: &serde_json::Value| v.as_str()).unwrap_or("...").to_string();
-                    }
-                }
+        // 2. Time of day
+        choices.extend(self.dialogues.time.phase(&self.state.time_of_day));
+
+        // 3. Lines targeted only by their condition
+        choices.extend(&self.dialogues.conditional);
+
+        // 4. Action
+        let action = action_override.or_else(|| self.actions.get(&self.state.last_action).ok());
+        if let Some(action) = action
+            && let Some(node) = self.dialogues.actions.get(&action.dialogue_key)
+        {
+            let list = node.tier(self.state.affection >= 40);
+            // Las acciones exclusivas (p. ej. dulces) sólo usan sus propios diálogos
+            if action_override.is_some()
+                && action.exclusive_dialogue
+                && list.iter().any(|l| l.is_available(&self.state))
+            {
+                choices.clear();
             }
+            choices.extend(list);
         }
 
-        if choices.is_empty() {
-            return "...".to_string();
-        }
-
-        let mut rng = rand::rng();
-        choices.choose(&mut rng).and_then(|v: &&serde_json::Value| v.as_str()).unwrap_or("...").to_string()
+        self.pick_line(choices)
     }
 }
 
@@ -245,38 +412,43 @@ impl Engine {
 mod tests {
     use super::*;
 
+    fn test_engine(save_name: &str) -> Engine {
+        Engine {
+            state: GameState::default(),
+            dialogues: DialogueDb::default(),
+            conversations: ConversationDb::default(),
+            actions: ActionRegistry::builtin(),
+            sprites: SpriteManifest::default(),
+            save_path: Some(std::env::temp_dir().join(save_name)),
+        }
+    }
+
     #[test]
     fn test_time_progression() {
-        let mut state = GameState::default();
-        state.hour = 22;
-        state.minute = 0;
-        state.day = 1;
-
-        let mut engine = Engine {
-            state: state.clone(),
-            dialogues: serde_json::Value::Null,
-            save_path: PathBuf::from("test_save.json"),
-        };
+        let mut engine = test_engine("test_save.json");
+        engine.state = GameState { hour: 22, minute: 0, day: 1, ..Default::default() };
 
         // Una acción toma 2 horas (120 min). 22:00 + 2h = 00:00 del día siguiente (8:00 por reset)
-        engine.interact("talk");
+        engine.interact("talk").unwrap();
         assert_eq!(engine.state.day, 2);
         assert_eq!(engine.state.hour, 8);
         assert_eq!(engine.state.minute, 0);
     }
 
+    #[test]
+    fn test_clock_minutes_day_zero() {
+        let state = GameState { day: 0, hour: 10, minute: 30, ..Default::default() };
+        assert_eq!(state.clock_minutes(), 630);
+    }
+
     #[test]
     fn test_daily_limits() {
-        let mut engine = Engine {
-            state: GameState::default(),
-            dialogues: serde_json::Value::Null,
-            save_path: PathBuf::from("test_save_limits.json"),
-        };
+        let mut engine = test_engine("test_save_limits.json");
 
         // Realizamos acciones hasta llegar a la noche sin cambiar de día todavía
         // 8:00, 10:00, 12:00, 14:00, 16:00, 18:00 (6 acciones)
         for _ in 0..6 {
-            engine.interact("pat_head");
+            engine.interact("pat_head").unwrap();
         }
         assert_eq!(engine.state.daily_affection, 10); // 6 * 2 = 12, pero el límite es 10
         assert_eq!(engine.state.affection, 10);
@@ -284,14 +456,36 @@ mod tests {
         assert_eq!(engine.state.hour, 20);
     }
 
+    #[test]
+    fn test_daily_limits_clamp_partial_gain() {
+        let mut engine = test_engine("test_save_clamp.json");
+
+        // 2 + 2 + 5 = 9: a la caricia siguiente sólo le queda 1 punto hasta el límite
+        for id in ["pat_head", "pat_head", "give_treat", "pat_head"] {
+            engine.interact(id).unwrap();
+        }
+        assert_eq!(engine.state.daily_affection, 10);
+        assert_eq!(engine.state.affection, 10);
+        assert_eq!(engine.state.daily_trust, 5);
+    }
+
+    #[test]
+    fn test_penalties_ignore_daily_limits() {
+        let mut engine = test_engine("test_save_penalty.json");
+        engine.state.daily_affection = 10;
+
+        // Con el límite alcanzado la pérdida se aplica igual, y no libera margen
+        engine.apply_gains(-3, 0);
+        assert_eq!((engine.state.affection, engine.state.daily_affection), (-3, 10));
+        engine.state.daily_affection = 4;
+        engine.apply_gains(-2, 5);
+        assert_eq!((engine.state.affection, engine.state.daily_affection), (-5, 4));
+        assert_eq!((engine.state.trust, engine.state.daily_trust), (5, 5));
+    }
+
     #[test]
     fn test_time_of_day_update() {
-        let state = GameState::default();
-        let mut engine = Engine {
-            state,
-            dialogues: serde_json::Value::Null,
-            save_path: PathBuf::from("test_save_2.json"),
-        };
+        let mut engine = test_engine("test_save_2.json");
 
         engine.state.hour = 5;
         engine.update_time_of_day();
@@ -312,27 +506,106 @@ mod tests {
 
     #[test]
     fn test_phase_change_dialogue() {
-        let mut engine = Engine {
-            state: GameState::default(),
-            dialogues: serde_json::from_str(r#"{
+        let mut engine = test_engine("test_save_phase.json");
+        engine.dialogues = DialogueDb::parse(r#"{
                 "time": {
                     "morning": ["morg"],
                     "afternoon": ["aft"],
                     "night": ["ngt"]
                 },
                 "actions": { "talk": { "low": ["..."], "high": ["..."] } }
-            }"#).unwrap(),
-            save_path: PathBuf::from("test_save_phase.json"),
-        };
+            }"#, Path::new("test")).unwrap();
 
         // Empieza a las 11:00 (morning)
         engine.state.hour = 11;
         engine.state.time_of_day = "morning".to_string();
 
         // Una acción (2h) -> 13:00 (afternoon)
-        engine.interact("talk");
+        engine.interact("talk").unwrap();
 
         assert_eq!(engine.state.time_of_day, "afternoon");
         assert_eq!(engine.state.last_dialogue, "aft");
     }
+
+    #[test]
+    fn test_unknown_action_is_rejected() {
+        let mut engine = test_engine("test_save_unknown.json");
+
+        let err = engine.interact("pat_haed").unwrap_err();
+        assert_eq!(err, ActionError::Unknown("pat_haed".to_string()));
+        assert_eq!(engine.state.hour, 8);
+        assert_eq!(engine.state.actions_taken, 0);
+    }
+
+    #[test]
+    fn test_dialogue_history_and_once() {
+        let mut engine = test_engine("test_save_history.json");
+        engine.dialogues = DialogueDb::parse(r#"{
+                "distrust": ["a", "b", { "text": "c", "weight": 5 }, { "id": "d", "text": "d", "once": true }],
+                "repeat_window": 1
+            }"#, Path::new("test")).unwrap();
+
+        let mut said = Vec::new();
+        for _ in 0..50 {
+            let line = engine.get_random_dialogue(None);
+            engine.say(line);
+            said.push(engine.state.last_dialogue.clone());
+        }
+
+        assert!(said.windows(2).all(|w| w[0] != w[1]));
+        assert!(said.iter().filter(|l| *l == "d").count() <= 1);
+        assert_eq!(engine.state.recent_dialogues.len(), 1);
+    }
+
+    #[test]
+    fn test_talk_conversation() {
+        let mut engine = test_engine("test_save_conversation.json");
+        engine.conversations = ConversationDb::parse(r#"{ "conversations": [{
+            "id": "c", "start": "a",
+            "nodes": {
+                "a": { "text": "¿Sí?", "choices": [
+                    { "label": "Nada", "next": "b" },
+                    { "label": "Oculta", "when": "day > 5" },
+                    { "label": "Abrazo", "affection": 3, "trust": 2, "next": "c" }
+                ] },
+                "b": { "text": "Vale..." },
+                "c": { "text": "¡!" }
+            }
+        }] }"#, Path::new("test")).unwrap();
+
+        engine.interact("talk").unwrap();
+        assert_eq!(engine.state.last_dialogue, "¿Sí?");
+        assert_eq!(engine.replies().len(), 2);
+        assert_eq!(engine.interact("pat_head"), Err(ActionError::InConversation));
+
+        engine.reply(1).unwrap();
+        assert_eq!(engine.state.last_dialogue, "¡!");
+        assert_eq!(engine.state.conversation, None);
+        assert_eq!(engine.state.affection, 4); // 1 por hablar + 3 por la respuesta
+        assert_eq!(engine.state.trust, 2);
+
+        let log: Vec<_> = engine.state.log.iter().map(|e| (e.action.as_str(), e.label.as_str(), e.affection, e.dialogue.as_str())).collect();
+        assert_eq!(log, vec![("talk", "Hablar", 1, "¿Sí?"), ("reply", "Abrazo", 3, "¡!")]);
+        assert_eq!(engine.reply(0), Err(ActionError::InvalidReply(0)));
+    }
+
+    #[test]
+    fn test_conversation_without_available_choices_ends() {
+        let mut engine = test_engine("test_save_conversation_hidden.json");
+        engine.conversations = ConversationDb::parse(r#"{ "conversations": [{
+            "id": "c", "start": "a",
+            "nodes": {
+                "a": { "text": "Hmm.", "choices": [
+                    { "label": "Oculta", "when": "day > 5" },
+                    { "label": "También", "when": "trust > 50" }
+                ] }
+            }
+        }] }"#, Path::new("test")).unwrap();
+
+        engine.interact("talk").unwrap();
+        assert_eq!(engine.state.last_dialogue, "Hmm.");
+        assert_eq!(engine.state.conversation, None);
+        assert!(engine.replies().is_empty());
+        engine.interact("pat_head").unwrap();
+    }
 }
@@ -0,0 +1,401 @@
+//! Órdenes sin interfaz a pantalla completa (`act`, `reply`, `status`, `log`, `update`,
+//! `dialogues lint`), pensadas para scripts, prompts del shell, barras de estado y CI.
+//!
+//! Con `--json` cada orden escribe un único objeto JSON en la salida estándar, también
+//! los errores (`{"error": "..."}`); el código de salida es 1 si algo falla. Las
+//! órdenes no terminan el proceso: devuelven el código a `main`.
+
+use crate::action::ActionRegistry;
+use crate::assets;
+use crate::config::UpdateConfig;
+use crate::engine::{Engine, LogEntry};
+use crate::i18n::{self, tr};
+use crate::lint::{self, Diagnostic, Severity};
+use crate::paths::Paths;
+use crate::save::{SaveError, SaveStore};
+use crate::update::{self, UpdateOutcome, VersionCheck};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Formato de salida elegido con `--json`.
+#[derive(Clone, Copy)]
+pub struct Output {
+    pub json: bool,
+}
+
+impl Output {
+    fn print<T: Serialize>(self, value: &T, text: impl FnOnce() -> String) {
+        let content = if self.json {
+            serde_json::to_string_pretty(value).unwrap_or_else(|e| error_json(tr!("error.json"), e))
+        } else {
+            text()
+        };
+        // Un lector que cierra la tubería antes de tiempo (`| head`) no es un error
+        let _ = writeln!(std::io::stdout().lock(), "{}", content);
+    }
+
+    /// Informa del error y devuelve el código de salida 1.
+    pub fn fail(self, context: &str, e: impl fmt::Display) -> ExitCode {
+        if self.json {
+            println!("{}", error_json(context, e));
+        } else {
+            eprintln!("{}: {}", context, e);
+        }
+        ExitCode::FAILURE
+    }
+}
+
+fn error_json(context: &str, e: impl fmt::Display) -> String {
+    serde_json::json!({ "error": format!("{}: {}", context, e) }).to_string()
+}
+
+/// Abre la ranura `slot`, o la partida más reciente si no se indica. Si no hay
+/// ninguna, se usa `principal`; `create` decide si se crea cuando no existe.
+pub fn open_slot(engine: &mut Engine, store: &SaveStore, slot: Option<&str>, create: bool) -> Result<String, SaveError> {
+    let name = match slot {
+        Some(name) => name.to_string(),
+        None => store
+            .list()?
+            .into_iter()
+            .find(|s| s.problem.is_none())
+            .map(|s| s.name)
+            .unwrap_or_else(|| "principal".to_string()),
+    };
+    let path = store.path(&name)?;
+    if !path.exists() {
+        if !create {
+            return Err(SaveError::NotFound(name));
+        }
+        store.create(&name)?;
+    }
+    if let Some(backup) = engine.open_save(path)? {
+        eprintln!("{}", tr!("saves.recovered", name = name, backup = backup.display()));
+    }
+    Ok(name)
+}
+
+#[derive(Serialize)]
+struct ActionStatus<'a> {
+    id: &'a str,
+    label: &'a str,
+    available: bool,
+    /// Por qué no está disponible (bloqueada o en enfriamiento).
+    reason: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Status<'a> {
+    slot: &'a str,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    time_of_day: &'a str,
+    affection: i32,
+    trust: i32,
+    daily_affection: i32,
+    daily_trust: i32,
+    daily_affection_cap: i32,
+    daily_trust_cap: i32,
+    mood: &'static str,
+    last_action: &'a str,
+    dialogue: &'a str,
+    /// Respuestas posibles si Sylvie espera una (ver `reply`).
+    replies: Vec<&'a str>,
+    actions: Vec<ActionStatus<'a>>,
+}
+
+impl<'a> Status<'a> {
+    fn new(engine: &'a Engine, slot: &'a str) -> Self {
+        let state = &engine.state;
+        Self {
+            slot,
+            day: state.day,
+            hour: state.hour,
+            minute: state.minute,
+            time_of_day: &state.time_of_day,
+            affection: state.affection,
+            trust: state.trust,
+            daily_affection: state.daily_affection,
+            daily_trust: state.daily_trust,
+            daily_affection_cap: engine.actions.caps.affection,
+            daily_trust_cap: engine.actions.caps.trust,
+            mood: engine.mood(),
+            last_action: &state.last_action,
+            dialogue: &state.last_dialogue,
+            replies: engine.replies().iter().map(|c| c.label.as_str()).collect(),
+            actions: engine
+                .actions
+                .iter()
+                .map(|a| {
+                    let reason = a.check(state).err().map(|e| e.to_string());
+                    ActionStatus { id: &a.id, label: &a.label, available: reason.is_none(), reason }
+                })
+                .collect(),
+        }
+    }
+
+    /// Una sola línea, apta para barras de estado.
+    fn line(&self) -> String {
+        tr!(
+            "status.line",
+            day = self.day,
+            time = format!("{}:{:02}", self.hour, self.minute),
+            phase = i18n::phase(self.time_of_day),
+            affection = self.affection,
+            daily_affection = self.daily_affection,
+            affection_cap = self.daily_affection_cap,
+            trust = self.trust,
+            daily_trust = self.daily_trust,
+            trust_cap = self.daily_trust_cap
+        )
+    }
+}
+
+pub fn status(out: Output, engine: &Engine, slot: &str) {
+    let status = Status::new(engine, slot);
+    out.print(&status, || status.line());
+}
+
+#[derive(Serialize)]
+struct Turn<'a> {
+    #[serde(flatten)]
+    entry: &'a LogEntry,
+    status: Status<'a>,
+}
+
+/// Resultado de `act` y `reply`: la última entrada del registro y el estado.
+fn print_turn(out: Output, engine: &Engine, slot: &str) {
+    let Some(entry) = engine.state.log.back() else { return };
+    let turn = Turn { entry, status: Status::new(engine, slot) };
+    out.print(&turn, || {
+        let mut text = format!("Sylvie: {}\n{}", entry.dialogue, turn.status.line());
+        for (i, reply) in turn.status.replies.iter().enumerate() {
+            text.push_str(&format!("\n  {}) {}", i + 1, reply));
+        }
+        if !turn.status.replies.is_empty() {
+            text.push_str(&format!("\n{}", tr!("headless.reply_hint")));
+        }
+        text
+    });
+}
+
+pub fn act(out: Output, engine: &mut Engine, slot: &str, action: &str) -> Result<(), Box<dyn Error>> {
+    engine.interact(action)?;
+    print_turn(out, engine, slot);
+    Ok(())
+}
+
+/// `n` empieza en 1, como en el menú (`main` rechaza el 0).
+pub fn reply(out: Output, engine: &mut Engine, slot: &str, n: usize) -> Result<(), Box<dyn Error>> {
+    engine.reply(n.saturating_sub(1))?;
+    print_turn(out, engine, slot);
+    Ok(())
+}
+
+pub fn log(out: Output, engine: &Engine, limit: usize) {
+    let log = &engine.state.log;
+    let entries: Vec<&LogEntry> = log.iter().skip(log.len().saturating_sub(limit)).collect();
+    out.print(&entries, || {
+        if entries.is_empty() {
+            return tr!("headless.empty_log").to_string();
+        }
+        entries
+            .iter()
+            .map(|e| {
+                tr!(
+                    "headless.log_entry",
+                    day = e.day,
+                    time = format!("{}:{:02}", e.hour, e.minute),
+                    label = e.label,
+                    affection = format!("{:+}", e.affection),
+                    trust = format!("{:+}", e.trust),
+                    dialogue = e.dialogue
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+}
+
+/// Qué hace `update`.
+pub enum UpdateMode<'a> {
+    Install { to: Option<&'a str> },
+    Check,
+    List,
+    Rollback,
+}
+
+/// Devuelve el código de salida: 1 si la búsqueda de versiones falla.
+pub fn update(out: Output, config: &UpdateConfig, mode: UpdateMode) -> Result<ExitCode, Box<dyn Error>> {
+    let source = config.source.open();
+    match mode {
+        UpdateMode::Install { to } => {
+            if !out.json {
+                println!("{}", tr!("update.searching", source = source.describe()));
+            }
+            let outcome = update::update(config, to, &mut |done, total| {
+                if out.json {
+                    return;
+                }
+                match total {
+                    Some(total) if total > 0 => print!("\r{}", tr!("update.progress_percent", percent = done * 100 / total)),
+                    _ => print!("\r{}", tr!("update.progress_kib", kib = done / 1024)),
+                }
+                let _ = std::io::stdout().flush();
+            })?;
+            out.print(&outcome, || match &outcome {
+                UpdateOutcome::Installed { .. } => format!("\n{}\n{}", outcome, tr!("update.rollback_hint")),
+                _ => outcome.to_string(),
+            });
+        }
+        UpdateMode::Check => {
+            // Los fallos salen como los de las demás órdenes (`{"error": "..."}` con `--json`)
+            let check = update::check_version(&*source, config);
+            if let VersionCheck::Error { message } = &check {
+                return Ok(out.fail(tr!("update.check_failed"), message));
+            }
+            out.print(&check, || check.to_string());
+        }
+        UpdateMode::List => {
+            let releases = update::list(config)?;
+            out.print(&releases, || {
+                if releases.is_empty() {
+                    return tr!("update.no_releases", source = source.describe());
+                }
+                let mut text = tr!("update.list_title", source = source.describe(), channel = config.channel.label());
+                let target = self_update::get_target();
+                for release in &releases {
+                    let mut tags = Vec::new();
+                    if release.current {
+                        tags.push(tr!("update.tag_current").to_string());
+                    }
+                    if release.prerelease {
+                        tags.push(tr!("update.tag_beta").to_string());
+                    }
+                    if release.pinned {
+                        tags.push(tr!("update.tag_pinned").to_string());
+                    } else if release.skipped {
+                        tags.push(tr!("update.tag_skipped").to_string());
+                    }
+                    if !release.available {
+                        tags.push(tr!("update.tag_no_binary", target = target));
+                    }
+                    let date = if release.date.is_empty() { "-" } else { &release.date };
+                    let line = format!("  v{:<16} {:<10}  {}", release.version, date, tags.join(", "));
+                    text.push('\n');
+                    text.push_str(line.trim_end());
+                }
+                text
+            });
+        }
+        UpdateMode::Rollback => {
+            let outcome = update::rollback()?;
+            out.print(&outcome, || outcome.to_string());
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Serialize)]
+struct LintedFile {
+    file: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Serialize)]
+struct LintReport {
+    files: Vec<LintedFile>,
+    errors: usize,
+    warnings: usize,
+}
+
+/// Archivos de diálogos que se revisan por defecto: los de cada idioma embebido o
+/// presente en el directorio de datos, con la misma prioridad que al jugar.
+fn default_dialogue_files(paths: &Paths) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let locale_of = |name: &str| name.strip_prefix("dialogues.")?.strip_suffix(".json").map(str::to_string);
+    let mut locales: BTreeSet<String> = assets::embedded_names().filter_map(locale_of).collect();
+    if let Ok(entries) = std::fs::read_dir(&paths.data_dir) {
+        locales.extend(entries.flatten().filter_map(|e| locale_of(&e.file_name().to_string_lossy())));
+    }
+    let mut files = Vec::new();
+    for locale in locales {
+        if let Some(asset) = assets::load_localized(paths, "dialogues.json", &locale)? {
+            files.push((asset.origin.display().to_string(), asset.text()?.to_string()));
+        }
+    }
+    Ok(files)
+}
+
+/// `dialogues lint`: devuelve el código de salida 1 si hay errores (o avisos, con `strict`).
+pub fn dialogues_lint(out: Output, paths: &Paths, files: &[PathBuf], strict: bool) -> Result<ExitCode, Box<dyn Error>> {
+    let actions = assets::load(paths, "actions.json")?;
+    let actions = ActionRegistry::parse(actions.text()?).map_err(|e| format!("{}: {}", actions.origin.display(), e))?;
+
+    let files = if files.is_empty() {
+        default_dialogue_files(paths)?
+    } else {
+        files
+            .iter()
+            .map(|f| Ok((f.display().to_string(), std::fs::read_to_string(f).map_err(|e| format!("{}: {}", f.display(), e))?)))
+            .collect::<Result<_, Box<dyn Error>>>()?
+    };
+
+    let files: Vec<LintedFile> = files
+        .into_iter()
+        .map(|(file, content)| LintedFile { diagnostics: lint::lint(&content, Path::new(&file), &actions), file })
+        .collect();
+    let count = |severity| files.iter().flat_map(|f| &f.diagnostics).filter(|d| d.severity == severity).count();
+    let report = LintReport { errors: count(Severity::Error), warnings: count(Severity::Warning), files };
+
+    out.print(&report, || {
+        let mut lines: Vec<String> = report
+            .files
+            .iter()
+            .flat_map(|f| f.diagnostics.iter().map(move |d| format!("{}: {}: {}: {}", f.file, d.severity.label(), d.key, d.message)))
+            .collect();
+        lines.push(tr!("lint.summary", files = report.files.len(), errors = report.errors, warnings = report.warnings));
+        lines.join("\n")
+    });
+    if report.errors > 0 || (strict && report.warnings > 0) {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths::Paths;
+
+    #[test]
+    fn test_open_slot_and_status() {
+        let dir = std::env::temp_dir().join("tfeeling_test_headless");
+        let _ = std::fs::remove_dir_all(&dir);
+        let paths = Paths { data_dir: dir.clone(), save_dir: dir.join("saves"), config_file: dir.join("config.toml"), cache_dir: dir.join("cache") };
+        let store = SaveStore::new(paths.save_dir.clone());
+        let mut engine = Engine::new(&paths).unwrap();
+
+        // Sin partidas, sólo `act` crea la ranura por defecto
+        assert!(matches!(open_slot(&mut engine, &store, None, false), Err(SaveError::NotFound(_))));
+        assert_eq!(open_slot(&mut engine, &store, None, true).unwrap(), "principal");
+        engine.interact("pat_head").unwrap();
+        store.create("otra").unwrap();
+
+        // Un turno guarda una sola vez: la copia más reciente es la partida de antes
+        let backups = crate::save::backup_paths(&store.path("principal").unwrap());
+        assert_eq!(crate::save::read_state(&backups[0]).unwrap().actions_taken, 0);
+        assert!(!backups[1].exists());
+
+        let slot = open_slot(&mut engine, &store, Some("principal"), false).unwrap();
+        let status = serde_json::to_value(Status::new(&engine, &slot)).unwrap();
+        assert_eq!(status["slot"], "principal");
+        assert_eq!(status["affection"], 2);
+        assert_eq!(status["actions"][0]["id"], "pat_head");
+        assert_eq!(engine.state.log.len(), 1);
+    }
+}
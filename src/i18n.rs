@@ -0,0 +1,172 @@
+//! Idioma de la interfaz y catálogos de traducción.
+//!
+//! Los textos de la interfaz están en `locales/<idioma>.json` (un objeto plano
+//! `"clave": "texto"`); como el resto de recursos, un archivo en el directorio de
+//! datos sustituye al embebido, y así se pueden añadir idiomas sin recompilar.
+//! Las claves que falten en un catálogo se toman del idioma por defecto.
+//!
+//! Los textos pueden llevar marcadores con nombre (`{name}`) que rellena `tr!`.
+
+use crate::assets;
+use crate::paths::Paths;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Idioma de los textos originales y de respaldo.
+pub const DEFAULT_LOCALE: &str = "es";
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// Traduce `key` y rellena sus marcadores: `tr!("saves.deleted", name = slot)`.
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::i18n::t($key)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::t($key), &[$((stringify!($name), &$value as &dyn std::fmt::Display)),+])
+    };
+}
+pub(crate) use tr;
+
+struct Catalog {
+    locale: String,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Catalog {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).or_else(|| self.fallback.get(key)).map(String::as_str)
+    }
+}
+
+#[derive(Debug)]
+pub struct LocaleError(String);
+
+impl fmt::Display for LocaleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for LocaleError {}
+
+fn load_strings(paths: &Paths, locale: &str) -> Result<Option<HashMap<String, String>>, LocaleError> {
+    // Sin catálogo todavía, así que nada de `tr!` aquí: lo inicializaría en español
+    let name = format!("locales/{}.json", locale);
+    let origin = paths.data_file(&name);
+    let Some(bytes) = assets::read(paths, &name).map_err(|e| LocaleError(format!("{}: {}", origin.display(), e)))? else {
+        return Ok(None);
+    };
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|e| LocaleError(format!("{}: {}", origin.display(), e)))
+}
+
+/// Idioma de una variable como `LANG` (`en_US.UTF-8` → `en`). `C` y `POSIX` no cuentan.
+pub fn normalize(value: &str) -> Option<String> {
+    let lang = value.split(['.', '@']).next()?.split(['_', '-']).next()?.trim().to_lowercase();
+    if lang.is_empty() || lang == "c" || lang == "posix" {
+        return None;
+    }
+    Some(lang)
+}
+
+/// Idioma pedido: `--lang` y, si no, `LC_ALL`, `LC_MESSAGES` o `LANG`, como gettext.
+pub fn detect(requested: Option<&str>, env: impl Fn(&str) -> Option<String>) -> String {
+    requested
+        .and_then(normalize)
+        .or_else(|| ["LC_ALL", "LC_MESSAGES", "LANG"].iter().find_map(|key| env(key).as_deref().and_then(normalize)))
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// Carga el catálogo de `locale`. Sin catálogo para ese idioma se usa el idioma por
+/// defecto; `explicit` indica si el idioma se pidió con `--lang` (y hay que avisar).
+pub fn init(paths: &Paths, locale: &str, explicit: bool) -> Result<(), LocaleError> {
+    let fallback = load_strings(paths, DEFAULT_LOCALE)?.unwrap_or_default();
+    let strings = load_strings(paths, locale)?;
+    let missing = strings.is_none();
+    let catalog = match strings {
+        Some(strings) => Catalog { locale: locale.to_string(), strings, fallback },
+        None => Catalog { locale: DEFAULT_LOCALE.to_string(), strings: HashMap::new(), fallback },
+    };
+    // Sólo se inicializa una vez, al arrancar
+    let _ = CATALOG.set(catalog);
+    if missing && explicit {
+        eprintln!("{}", tr!("i18n.missing_locale", locale = locale, fallback = DEFAULT_LOCALE));
+    }
+    Ok(())
+}
+
+fn catalog() -> &'static Catalog {
+    CATALOG.get_or_init(|| {
+        let strings = assets::embedded(&format!("locales/{}.json", DEFAULT_LOCALE))
+            .and_then(|bytes| serde_json::from_slice(bytes).ok())
+            .unwrap_or_default();
+        Catalog { locale: DEFAULT_LOCALE.to_string(), strings, fallback: HashMap::new() }
+    })
+}
+
+/// Idioma en uso.
+pub fn locale() -> &'static str {
+    &catalog().locale
+}
+
+/// Texto de `key` en el idioma actual, en el idioma por defecto o, si tampoco, la propia clave.
+pub fn t(key: &str) -> &str {
+    catalog().get(key).unwrap_or(key)
+}
+
+/// Nombre de una fase del día (`morning` → `mañana`).
+pub fn phase(key: &str) -> &str {
+    catalog().get(&format!("phase.{}", key)).unwrap_or(key)
+}
+
+/// Traducción de un texto que viene en los propios datos (como las etiquetas de
+/// `actions.json`, escritas en el idioma por defecto). Sólo se traduce si el idioma
+/// actual es otro y su catálogo tiene la clave; si no, se respeta el original.
+pub fn data_text(key: &str) -> Option<&'static str> {
+    let catalog = catalog();
+    catalog.strings.get(key).filter(|_| catalog.locale != DEFAULT_LOCALE).map(String::as_str)
+}
+
+/// Sustituye cada `{nombre}` de `template` por su valor.
+pub fn fill(template: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_locale() {
+        let env = |key: &str| match key {
+            "LANG" => Some("en_GB.UTF-8".to_string()),
+            "LC_MESSAGES" => Some("C".to_string()),
+            _ => None,
+        };
+        assert_eq!(detect(None, env), "en");
+        assert_eq!(detect(Some("es-MX"), env), "es");
+        assert_eq!(detect(None, |_| None), DEFAULT_LOCALE);
+        assert_eq!(normalize("pt_BR@euro").as_deref(), Some("pt"));
+        assert_eq!(normalize("POSIX"), None);
+    }
+
+    #[test]
+    fn test_catalogs_share_keys() {
+        let es: HashMap<String, String> = serde_json::from_slice(assets::embedded("locales/es.json").unwrap()).unwrap();
+        let en: HashMap<String, String> = serde_json::from_slice(assets::embedded("locales/en.json").unwrap()).unwrap();
+        let mut missing: Vec<&String> = es.keys().filter(|k| !en.contains_key(*k)).collect();
+        missing.extend(en.keys().filter(|k| !es.contains_key(*k)));
+        assert!(missing.is_empty(), "claves sin traducir: {:?}", missing);
+
+        assert_eq!(fill(&es["saves.deleted"], &[("name", &"principal")]), "Partida 'principal' borrada.");
+        assert_eq!(t("no.existe"), "no.existe");
+    }
+}
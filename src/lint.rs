@@ -0,0 +1,187 @@
+//! Revisión de los archivos de diálogos (`tfeeling-CLI dialogues lint`).
+//!
+//! Además del esquema (lo mismo que se comprueba al arrancar), busca lo que el juego
+//! aceptaría pero el jugador notaría: grupos de acciones que no existen, acciones
+//! sin diálogos, grupos vacíos, líneas repetidas, líneas que son sólo un marcador
+//! (como "...") y líneas que no caben en el cuadro de diálogo.
+
+use crate::action::ActionRegistry;
+use crate::condition::Condition;
+use crate::dialogue::{DialogueDb, DialogueLine};
+use crate::i18n::tr;
+use serde::Serialize;
+use std::path::Path;
+
+/// Tamaño útil del cuadro de diálogo en un terminal de 80×24 con el retrato visible.
+const BOX_WIDTH: usize = 46;
+const BOX_HEIGHT: usize = 11;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Hace fallar la revisión.
+    Error,
+    /// Sólo la hace fallar con `--strict`.
+    Warning,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Error => tr!("lint.error"),
+            Severity::Warning => tr!("lint.warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Clave afectada, como en los errores de carga (p. ej. `actions.talk.low[3]`).
+    pub key: String,
+    pub message: String,
+}
+
+/// Filas que ocupa `text` en un cuadro de `width` columnas, partiendo por palabras.
+fn wrapped_rows(text: &str, width: usize) -> usize {
+    text.split('\n')
+        .map(|paragraph| {
+            let mut rows = 1;
+            let mut col = 0;
+            for word in paragraph.split_whitespace() {
+                let len = word.chars().count();
+                if col > 0 && col + 1 + len > width {
+                    rows += 1;
+                    col = 0;
+                }
+                col += if col > 0 { len + 1 } else { len };
+                // Una palabra más ancha que el cuadro se corta en varias filas
+                while col > width {
+                    rows += 1;
+                    col -= width;
+                }
+            }
+            rows
+        })
+        .sum()
+}
+
+/// Sólo puntuación: es lo que dice Sylvie cuando no encuentra ninguna línea.
+fn is_placeholder(text: &str) -> bool {
+    text.chars().all(|c| c.is_whitespace() || c.is_ascii_punctuation() || c == '…')
+}
+
+/// Revisa el contenido de un archivo de diálogos contra las acciones de `actions`.
+pub fn lint(content: &str, path: &Path, actions: &ActionRegistry) -> Vec<Diagnostic> {
+    let db = match DialogueDb::parse(content, path) {
+        Ok(db) => db,
+        Err(e) => {
+            let message = if e.line > 0 { tr!("lint.schema", line = e.line, column = e.column, error = e.message) } else { e.message };
+            return vec![Diagnostic { severity: Severity::Error, key: e.key, message }];
+        }
+    };
+    let mut found = Vec::new();
+    let mut report = |severity, key: String, message: String| found.push(Diagnostic { severity, key, message });
+
+    let known: Vec<&str> = actions.iter().map(|a| a.dialogue_key.as_str()).collect();
+    for key in db.actions.keys().filter(|k| !known.contains(&k.as_str())) {
+        report(Severity::Error, format!("actions.{}", key), tr!("lint.unknown_action", key = key, known = known.join(", ")));
+    }
+    for action in actions.iter().filter(|a| !db.actions.contains_key(&a.dialogue_key)) {
+        report(Severity::Warning, format!("actions.{}", action.dialogue_key), tr!("lint.missing_action", id = action.id));
+    }
+
+    // Primera aparición de cada línea, para avisar de las repetidas. Las condiciones
+    // se comparan ya interpretadas, así que no cuentan los espacios ni los paréntesis.
+    let mut seen: Vec<((&str, Option<&Condition>), String)> = Vec::new();
+    for (bucket, lines) in db.buckets() {
+        // `conditional` puede estar vacío: sus líneas son opcionales
+        if lines.is_empty() && bucket != "conditional" {
+            report(Severity::Warning, bucket.clone(), tr!("lint.empty_bucket").to_string());
+        }
+        for (i, line) in lines.iter().enumerate() {
+            let key = format!("{}[{}]", bucket, i);
+            let DialogueLine { text, when, .. } = line;
+            if text.trim().is_empty() {
+                report(Severity::Error, key, tr!("lint.empty_line").to_string());
+                continue;
+            }
+            if is_placeholder(text) {
+                report(Severity::Warning, key.clone(), tr!("lint.placeholder", text = text));
+            }
+            let rows = wrapped_rows(text, BOX_WIDTH);
+            if rows > BOX_HEIGHT {
+                report(Severity::Error, key.clone(), tr!("lint.too_long", rows = rows, max = BOX_HEIGHT));
+            }
+            // Dos líneas iguales con distinta condición no se consideran repetidas
+            let id = (text.trim(), when.as_ref());
+            match seen.iter().find(|(other, _)| *other == id).map(|(_, first)| first) {
+                Some(first) if first.starts_with(&format!("{}[", bucket)) => {
+                    report(Severity::Error, key, tr!("lint.duplicate", first = first));
+                }
+                Some(first) => report(Severity::Warning, key, tr!("lint.duplicate_elsewhere", first = first)),
+                None => seen.push((id, key)),
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint_str(content: &str) -> Vec<Diagnostic> {
+        lint(content, Path::new("dialogues.json"), &ActionRegistry::builtin())
+    }
+
+    #[test]
+    fn test_bundled_dialogues_have_no_errors() {
+        for name in ["dialogues.es.json", "dialogues.en.json"] {
+            let content = std::str::from_utf8(crate::assets::embedded(name).unwrap()).unwrap();
+            let errors: Vec<_> = lint(content, Path::new(name), &ActionRegistry::builtin())
+                .into_iter()
+                .filter(|d| d.severity == Severity::Error)
+                .collect();
+            assert!(errors.is_empty(), "{}: {:?}", name, errors);
+        }
+    }
+
+    #[test]
+    fn test_lint_rules() {
+        let long = "palabra ".repeat(80);
+        let content = format!(
+            r#"{{
+                "distrust": ["Hola", "...", "Hola", ""],
+                "neutral": ["Hola", {{ "text": "Hola", "when": "day > 2" }}, "{}"],
+                "actions": {{ "pat_haed": {{ "low": ["a"] }}, "pat_head": {{ "low": ["b"] }} }}
+            }}"#,
+            long
+        );
+        let found: Vec<(Severity, String)> = lint_str(&content).into_iter().map(|d| (d.severity, d.key)).collect();
+        let has = |severity, key: &str| found.contains(&(severity, key.to_string()));
+
+        assert!(has(Severity::Error, "actions.pat_haed"));
+        assert!(has(Severity::Warning, "actions.talk"));
+        assert!(has(Severity::Warning, "actions.pat_head.high"));
+        assert!(has(Severity::Warning, "trust"));
+        assert!(has(Severity::Warning, "distrust[1]"));
+        assert!(has(Severity::Error, "distrust[2]"));
+        assert!(has(Severity::Error, "distrust[3]"));
+        assert!(has(Severity::Warning, "neutral[0]"));
+        assert!(!found.iter().any(|(_, k)| k == "neutral[1]"));
+        assert!(has(Severity::Error, "neutral[2]"));
+        assert!(!found.iter().any(|(_, k)| k == "conditional"));
+
+        // La misma condición escrita de otra forma sigue siendo la misma línea
+        let spaced = lint_str(r#"{ "trust": [
+                { "text": "Hola", "when": "day > 2 && trust >= 5" },
+                { "text": "Hola", "when": "(day>2) && (trust >= 5)" }
+            ] }"#);
+        assert!(spaced.iter().any(|d| d.severity == Severity::Error && d.key == "trust[1]"));
+
+        let schema = lint_str(r#"{ "trsut": [] }"#);
+        assert_eq!(schema.len(), 1);
+        assert!(schema[0].message.contains("trsut"));
+    }
+}
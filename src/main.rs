@@ -1,8 +1,31 @@
+mod action;
+mod assets;
+mod audio;
+mod condition;
+mod config;
+mod conversation;
+mod dialogue;
 mod engine;
+mod headless;
+mod i18n;
+mod lint;
+mod paths;
+mod release;
+mod save;
+mod sprite;
+mod typewriter;
 mod ui;
 mod update;
+mod verify;
 
+use crate::audio::Audio;
+use crate::config::{Config, SessionConfig};
 use crate::engine::Engine;
+use crate::headless::{Output, UpdateMode};
+use crate::i18n::tr;
+use crate::paths::Paths;
+use crate::save::SaveStore;
+use crate::typewriter::TextSpeed;
 use clap::{Parser, Subcommand};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
@@ -10,43 +33,225 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{error::Error, io};
+use std::{error::Error, io, path::PathBuf, process::ExitCode, time::Duration};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Directorio de las partidas guardadas
+    #[arg(long, global = true, env = "TFEELING_SAVE_DIR", value_name = "DIR")]
+    save_dir: Option<PathBuf>,
+
+    /// Directorio de datos del juego (diálogos, acciones, arte)
+    #[arg(long, global = true, env = "TFEELING_DATA_DIR", value_name = "DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Archivo de configuración
+    #[arg(long, global = true, env = "TFEELING_CONFIG", value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Velocidad del texto; sustituye a la de la configuración
+    #[arg(long, value_enum)]
+    text_speed: Option<TextSpeed>,
+
+    /// No buscar actualizaciones al arrancar
+    #[arg(long)]
+    no_update_check: bool,
+
+    /// Borra el diálogo automáticamente tras mostrarse completo durante SEG segundos (0 lo desactiva)
+    #[arg(long, value_name = "SEG")]
+    auto_advance: Option<u64>,
+
+    /// Partida que usan las órdenes sin interfaz (por defecto, la más reciente)
+    #[arg(long, global = true, env = "TFEELING_SLOT", value_name = "NOMBRE")]
+    slot: Option<String>,
+
+    /// Salida en JSON para las órdenes sin interfaz
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Idioma de la interfaz y de los diálogos (p. ej. `es`, `en`); por defecto, el de `LANG`
+    #[arg(long, global = true, env = "TFEELING_LANG", value_name = "IDIOMA")]
+    lang: Option<String>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Actualiza la CLI desde GitHub
-    Update,
+    /// Actualiza la CLI desde la fuente configurada (GitHub por defecto)
+    Update {
+        /// Restaura el binario anterior a la última actualización
+        #[arg(long, conflicts_with_all = ["to", "list", "check"])]
+        rollback: bool,
+
+        /// Instala esta versión concreta, aunque sea anterior a la actual
+        #[arg(long, value_name = "VERSIÓN", conflicts_with_all = ["list", "check"])]
+        to: Option<String>,
+
+        /// Lista las versiones publicadas con su fecha
+        #[arg(long, conflicts_with = "check")]
+        list: bool,
+
+        /// Sólo comprueba si hay una versión nueva
+        #[arg(long)]
+        check: bool,
+    },
+    #[command(flatten)]
+    Game(GameCommand),
+    /// Herramientas para quienes escriben los diálogos
+    Dialogues {
+        #[command(subcommand)]
+        command: DialoguesCommand,
+    },
+}
+
+/// Órdenes que juegan sobre una partida sin abrir la interfaz.
+#[derive(Subcommand)]
+enum GameCommand {
+    /// Realiza una acción (p. ej. `pat_head`) en la partida y muestra la respuesta de Sylvie
+    Act {
+        /// Id de la acción, como en `actions.json`
+        action: String,
+    },
+    /// Elige una respuesta de la conversación en curso (empezando en 1)
+    Reply {
+        #[arg(value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        index: usize,
+    },
+    /// Muestra el estado de la partida
+    Status,
+    /// Muestra las últimas acciones de la partida
+    Log {
+        /// Número de entradas
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+    },
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+#[derive(Subcommand)]
+enum DialoguesCommand {
+    /// Revisa los archivos de diálogos; termina con código 1 si encuentra errores
+    Lint {
+        /// Archivos que revisar (por defecto, los de cada idioma del directorio de datos)
+        files: Vec<PathBuf>,
+
+        /// Los avisos también cuentan como errores
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
     let cli = Cli::parse();
 
-    if let Some(Commands::Update) = cli.command {
-        return update::update();
+    let paths = Paths::resolve(cli.data_dir, cli.save_dir, cli.config);
+
+    let locale = i18n::detect(cli.lang.as_deref(), |key| std::env::var(key).ok());
+    if let Err(e) = i18n::init(&paths, &locale, cli.lang.is_some()) {
+        // Sin catálogo no hay `tr!`: el aviso va en los dos idiomas incluidos
+        eprintln!("Error al cargar las traducciones / Could not load translations: {}", e);
+        return Ok(ExitCode::FAILURE);
     }
 
-    // Auto-check for update before starting
-    println!("Comprobando actualizaciones...");
-    match update::check_version() {
-        Ok(msg) if msg.contains("Nueva versión disponible") => {
-            println!("{}", msg);
-            println!("¿Deseas actualizar ahora? (s/n)");
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input)?;
-            if input.trim().to_lowercase() == "s" {
-                return update::update();
-            }
+    let saved_config = match Config::load(&paths.config_file) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}: {}", tr!("error.config"), e);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    // Las opciones de la línea de órdenes sólo valen para esta sesión
+    let mut config = saved_config.clone();
+    if let Some(speed) = cli.text_speed {
+        config.text.speed = speed;
+    }
+    if let Some(secs) = cli.auto_advance {
+        config.text.auto_advance = secs;
+    }
+
+    let out = Output { json: cli.json };
+    let command = match cli.command {
+        Some(Commands::Update { rollback, to, list, check }) => {
+            let mode = if rollback {
+                UpdateMode::Rollback
+            } else if list {
+                UpdateMode::List
+            } else if check {
+                UpdateMode::Check
+            } else {
+                UpdateMode::Install { to: to.as_deref() }
+            };
+            return Ok(headless::update(out, &config.updates, mode).unwrap_or_else(|e| {
+                let context = if out.json { tr!("update.error").to_string() } else { format!("\n{}", tr!("update.error")) };
+                out.fail(&context, e)
+            }));
         }
-        _ => {} // Continue if up to date or error
+        // Antes de cargar el motor: un archivo de diálogos roto es justo lo que hay que revisar
+        Some(Commands::Dialogues { command: DialoguesCommand::Lint { files, strict } }) => {
+            return Ok(headless::dialogues_lint(out, &paths, &files, strict).unwrap_or_else(|e| out.fail(tr!("error.generic"), e)));
+        }
+        Some(Commands::Game(command)) => Some(command),
+        None => None,
+    };
+
+    // Create game engine
+    let mut engine = match Engine::new(&paths) {
+        Ok(engine) => engine,
+        Err(e) => return Ok(out.fail(tr!("error.load_data"), e)),
+    };
+
+    let store = SaveStore::new(paths.save_dir.clone());
+
+    if let Some(command) = command {
+        // Sólo `act` empieza una partida si aún no existe
+        let create = matches!(command, GameCommand::Act { .. });
+        let slot = match headless::open_slot(&mut engine, &store, cli.slot.as_deref(), create) {
+            Ok(slot) => slot,
+            Err(e) => return Ok(out.fail(tr!("error.open_save"), e)),
+        };
+        let result = match command {
+            GameCommand::Act { action } => headless::act(out, &mut engine, &slot, &action),
+            GameCommand::Reply { index } => headless::reply(out, &mut engine, &slot, index),
+            GameCommand::Status => {
+                headless::status(out, &engine, &slot);
+                Ok(())
+            }
+            GameCommand::Log { limit } => {
+                headless::log(out, &engine, limit);
+                Ok(())
+            }
+        };
+        return Ok(match result {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => out.fail(tr!("error.generic"), e),
+        });
+    }
+
+    // Las partidas antiguas se importan una sola vez, al abrir el juego; las órdenes
+    // sin interfaz no tocan nada más que su ranura
+    if let Err(e) = store.import_legacy_dir(&paths::home_dir().join(".sylvie_saves")) {
+        eprintln!("{}: {}", tr!("error.import_legacy"), e);
+    }
+    if let Err(e) = store.import_legacy(&paths::home_dir().join(".sylvie_save.json")) {
+        eprintln!("{}: {}", tr!("error.import_legacy"), e);
     }
 
+    let audio = match Audio::new(&paths, &config.audio) {
+        Ok(audio) => audio,
+        Err(e) => {
+            eprintln!("{}: {}", tr!("error.load_data"), e);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    // La búsqueda de actualizaciones no bloquea el arranque: el aviso llega a la TUI
+    let update_notice = (config.updates.check_on_startup && !cli.no_update_check).then(|| {
+        let interval = Duration::from_secs(config.updates.interval_hours * 3600);
+        update::check_in_background(config.updates.clone(), paths.cache_dir.join("update-check.json"), interval)
+    });
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -54,11 +259,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Create game engine
-    let engine = Engine::new();
-
     // Run app
-    let res = ui::run_app(&mut terminal, engine);
+    let res = ui::run_app(&mut terminal, engine, audio, SessionConfig { saved: saved_config, effective: config }, update_notice, store, &paths);
 
     // Restore terminal
     disable_raw_mode()?;
@@ -73,5 +275,5 @@ fn main() -> Result<(), Box<dyn Error>> {
         eprintln!("Error: {:?}", err);
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
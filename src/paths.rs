@@ -0,0 +1,117 @@
+//! Ubicación de los datos del juego y de las partidas.
+//!
+//! Orden de búsqueda del directorio de datos (`dialogues.es.json`, `art/`...):
+//! 1. `--data-dir` o la variable `TFEELING_DATA_DIR`.
+//! 2. `./modules`, si existe (ejecución desde el repositorio).
+//! 3. `modules/` junto al ejecutable.
+//! 4. `tfeeling-CLI/modules` dentro de `$XDG_DATA_HOME` (por defecto `~/.local/share`)
+//!    o de cualquiera de `$XDG_DATA_DIRS` (por defecto `/usr/local/share:/usr/share`).
+//!
+//! Las partidas se guardan en `--save-dir`, `TFEELING_SAVE_DIR` o, si no,
+//! en `$XDG_DATA_HOME/tfeeling-CLI/saves`.
+//!
+//! La configuración se lee de `--config`, `TFEELING_CONFIG` o, si no,
+//! de `$XDG_CONFIG_HOME/tfeeling-CLI/config.toml` (por defecto `~/.config`).
+//! Los datos descartables (como la última búsqueda de actualizaciones) van a
+//! `$XDG_CACHE_HOME/tfeeling-CLI` (por defecto `~/.cache`).
+
+use std::path::{Path, PathBuf};
+
+const APP_DIR: &str = "tfeeling-CLI";
+
+#[derive(Clone)]
+pub struct Paths {
+    pub data_dir: PathBuf,
+    pub save_dir: PathBuf,
+    pub config_file: PathBuf,
+    pub cache_dir: PathBuf,
+}
+
+impl Paths {
+    /// Resuelve las rutas; los argumentos ya incluyen las variables de entorno (vía clap).
+    pub fn resolve(data_dir: Option<PathBuf>, save_dir: Option<PathBuf>, config_file: Option<PathBuf>) -> Self {
+        let env = |key: &str| std::env::var(key).ok();
+        let data_home = xdg_data_home(env);
+
+        let data_dir = data_dir.unwrap_or_else(|| {
+            let exe_dir = std::env::current_exe().ok().and_then(|p| p.parent().map(Path::to_path_buf));
+            let mut candidates = vec![PathBuf::from("modules")];
+            candidates.extend(exe_dir.map(|d| d.join("modules")));
+            candidates.push(data_home.join(APP_DIR).join("modules"));
+            candidates.extend(xdg_data_dirs(env).into_iter().map(|d| d.join(APP_DIR).join("modules")));
+
+            candidates
+                .into_iter()
+                .find(|dir| dir.is_dir())
+                .unwrap_or_else(|| data_home.join(APP_DIR).join("modules"))
+        });
+        let save_dir = save_dir.unwrap_or_else(|| data_home.join(APP_DIR).join("saves"));
+        let config_file = config_file.unwrap_or_else(|| xdg_config_home(env).join(APP_DIR).join("config.toml"));
+        let cache_dir = xdg_home(env, "XDG_CACHE_HOME", ".cache").join(APP_DIR);
+
+        Self { data_dir, save_dir, config_file, cache_dir }
+    }
+
+    pub fn data_file(&self, name: &str) -> PathBuf {
+        self.data_dir.join(name)
+    }
+}
+
+pub fn home_dir() -> PathBuf {
+    PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| ".".to_string()))
+}
+
+/// Valor de la variable XDG `key`, o `~/<default>` si no está definida o no es absoluta.
+fn xdg_home(env: impl Fn(&str) -> Option<String>, key: &str, default: &str) -> PathBuf {
+    env(key)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| PathBuf::from(env("HOME").unwrap_or_else(|| ".".to_string())).join(default))
+}
+
+fn xdg_data_home(env: impl Fn(&str) -> Option<String>) -> PathBuf {
+    xdg_home(env, "XDG_DATA_HOME", ".local/share")
+}
+
+fn xdg_config_home(env: impl Fn(&str) -> Option<String>) -> PathBuf {
+    xdg_home(env, "XDG_CONFIG_HOME", ".config")
+}
+
+fn xdg_data_dirs(env: impl Fn(&str) -> Option<String>) -> Vec<PathBuf> {
+    let dirs = env("XDG_DATA_DIRS")
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs.split(':').map(PathBuf::from).filter(|p| p.is_absolute()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xdg_defaults() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| vars.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string())
+        };
+
+        assert_eq!(xdg_data_home(env(&[("HOME", "/home/ana")])), PathBuf::from("/home/ana/.local/share"));
+        assert_eq!(
+            xdg_data_home(env(&[("HOME", "/home/ana"), ("XDG_DATA_HOME", "/datos")])),
+            PathBuf::from("/datos")
+        );
+        // Las rutas relativas se ignoran, como indica la especificación XDG
+        assert_eq!(
+            xdg_data_home(env(&[("HOME", "/home/ana"), ("XDG_DATA_HOME", "datos")])),
+            PathBuf::from("/home/ana/.local/share")
+        );
+        assert_eq!(xdg_config_home(env(&[("HOME", "/home/ana")])), PathBuf::from("/home/ana/.config"));
+        assert_eq!(
+            xdg_home(env(&[("HOME", "/home/ana"), ("XDG_CACHE_HOME", "/tmp/cache")]), "XDG_CACHE_HOME", ".cache"),
+            PathBuf::from("/tmp/cache")
+        );
+        assert_eq!(
+            xdg_data_dirs(env(&[("XDG_DATA_DIRS", "/opt/share:relativo:/usr/share")])),
+            vec![PathBuf::from("/opt/share"), PathBuf::from("/usr/share")]
+        );
+    }
+}
@@ -0,0 +1,399 @@
+//! De dónde saca el actualizador las versiones publicadas.
+//!
+//! La fuente se elige en `[updates.source]` de `config.toml`:
+//! - `github`: las releases de un repositorio de GitHub (por defecto, el oficial).
+//! - `http`: un espejo propio que sirve un `releases.json` (ver `ReleaseIndex`).
+//! - `local`: un directorio con `releases.json` o con tarballs
+//!   `tfeeling-CLI-<versión>-<target>.tar.gz`, o uno de esos archivos directamente.
+//!
+//! Cada versión debería incluir `SHA256SUMS` (ver `verify.rs`).
+
+use crate::i18n::tr;
+use crate::save;
+use crate::verify;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+pub const BIN_NAME: &str = "tfeeling-CLI";
+const INDEX_FILE: &str = "releases.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum SourceConfig {
+    Github { owner: String, repo: String },
+    /// URL base del espejo; el índice está en `<url>/releases.json`.
+    Http { url: String },
+    Local { path: PathBuf },
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        SourceConfig::Github { owner: "staFF6773".to_string(), repo: "tfeeling-CLI".to_string() }
+    }
+}
+
+impl SourceConfig {
+    pub fn open(&self) -> Box<dyn ReleaseSource> {
+        match self {
+            SourceConfig::Github { owner, repo } => Box::new(GitHubSource { owner: owner.clone(), repo: repo.clone() }),
+            SourceConfig::Http { url } => Box::new(HttpSource { base: url.trim_end_matches('/').to_string() }),
+            SourceConfig::Local { path } => Box::new(LocalSource { path: path.clone() }),
+        }
+    }
+}
+
+/// Qué versiones ofrece el actualizador.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    /// Sólo versiones estables.
+    #[default]
+    Stable,
+    /// También las preliminares (`1.3.0-beta.1`, `2.0.0-rc.1`...).
+    Beta,
+}
+
+impl Channel {
+    pub fn allows(self, version: &semver::Version) -> bool {
+        self == Channel::Beta || version.pre.is_empty()
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            Channel::Stable => Channel::Beta,
+            Channel::Beta => Channel::Stable,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Channel::Stable => tr!("channel.stable"),
+            Channel::Beta => tr!("channel.beta"),
+        }
+    }
+}
+
+/// Interpreta una versión, con o sin `v` delante.
+pub fn parse_version(version: &str) -> Option<semver::Version> {
+    semver::Version::parse(version.trim().trim_start_matches('v')).ok()
+}
+
+/// Una versión publicada. Es también el formato de cada entrada de `releases.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Release {
+    pub version: String,
+    /// Fecha de publicación, `AAAA-MM-DD`.
+    #[serde(default)]
+    pub date: String,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+/// Archivo descargable de una versión. En `releases.json`, `url` puede ser
+/// relativa a la ubicación del índice.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub url: String,
+}
+
+/// Contenido de `releases.json`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReleaseIndex {
+    releases: Vec<Release>,
+}
+
+impl Release {
+    pub fn semver(&self) -> Option<semver::Version> {
+        parse_version(&self.version)
+    }
+
+    /// El tarball para `target` (p. ej. `x86_64-unknown-linux-gnu`).
+    pub fn asset_for(&self, target: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|a| a.name.contains(target) && a.name.ends_with(".tar.gz"))
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+}
+
+#[derive(Debug)]
+pub enum UpdateError {
+    Io(io::Error),
+    Network(String),
+    Index { origin: String, message: String },
+    NoAsset { version: String, target: String },
+    Archive(String),
+    /// Suma o firma ausente o incorrecta.
+    Verification(String),
+    /// `update --rollback` sin una actualización previa.
+    NoPrevious(PathBuf),
+    /// `update --to` con una versión que la fuente no publica.
+    UnknownVersion(String),
+}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UpdateError::Io(e) => write!(f, "{}", e),
+            UpdateError::Network(msg) => write!(f, "{}", tr!("update.error_network", error = msg)),
+            UpdateError::Index { origin, message } => write!(f, "{}: {}", origin, message),
+            UpdateError::NoAsset { version, target } => write!(f, "{}", tr!("update.error_no_asset", version = version, target = target)),
+            UpdateError::Archive(msg) => write!(f, "{}", tr!("update.error_archive", error = msg)),
+            UpdateError::Verification(msg) => write!(f, "{}", tr!("update.error_verification", error = msg)),
+            UpdateError::NoPrevious(path) => write!(f, "{}", tr!("update.error_no_previous", path = path.display())),
+            UpdateError::UnknownVersion(version) => write!(f, "{}", tr!("update.error_unknown_version", version = version)),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+impl From<io::Error> for UpdateError {
+    fn from(e: io::Error) -> Self {
+        UpdateError::Io(e)
+    }
+}
+
+impl From<reqwest::Error> for UpdateError {
+    fn from(e: reqwest::Error) -> Self {
+        UpdateError::Network(e.to_string())
+    }
+}
+
+impl From<self_update::errors::Error> for UpdateError {
+    fn from(e: self_update::errors::Error) -> Self {
+        UpdateError::Network(e.to_string())
+    }
+}
+
+/// Progreso de una descarga: bytes recibidos y total, si se conoce.
+pub type Progress<'a> = &'a mut dyn FnMut(u64, Option<u64>);
+
+pub trait ReleaseSource {
+    /// Descripción para los mensajes ("GitHub staFF6773/tfeeling-CLI", una URL...).
+    fn describe(&self) -> String;
+    /// Versiones publicadas, en cualquier orden.
+    fn releases(&self) -> Result<Vec<Release>, UpdateError>;
+    /// Copia el contenido de `asset` en `dest`.
+    fn download(&self, asset: &ReleaseAsset, dest: &mut dyn Write, progress: Progress) -> Result<(), UpdateError>;
+}
+
+struct GitHubSource {
+    owner: String,
+    repo: String,
+}
+
+impl ReleaseSource for GitHubSource {
+    fn describe(&self) -> String {
+        format!("GitHub {}/{}", self.owner, self.repo)
+    }
+
+    fn releases(&self) -> Result<Vec<Release>, UpdateError> {
+        let releases = self_update::backends::github::ReleaseList::configure()
+            .repo_owner(&self.owner)
+            .repo_name(&self.repo)
+            .build()?
+            .fetch()?;
+        Ok(releases
+            .into_iter()
+            .map(|r| Release {
+                version: r.version,
+                date: r.date.chars().take(10).collect(),
+                notes: r.body.unwrap_or_default(),
+                assets: r.assets.into_iter().map(|a| ReleaseAsset { name: a.name, url: a.download_url }).collect(),
+            })
+            .collect())
+    }
+
+    fn download(&self, asset: &ReleaseAsset, dest: &mut dyn Write, progress: Progress) -> Result<(), UpdateError> {
+        // La URL de la API devuelve el archivo sólo si se pide como binario
+        let request = http_client()?.get(&asset.url).header(reqwest::header::ACCEPT, "application/octet-stream");
+        http_download(request, dest, progress)
+    }
+}
+
+struct HttpSource {
+    base: String,
+}
+
+impl ReleaseSource for HttpSource {
+    fn describe(&self) -> String {
+        self.base.clone()
+    }
+
+    fn releases(&self) -> Result<Vec<Release>, UpdateError> {
+        let url = format!("{}/{}", self.base, INDEX_FILE);
+        let content = http_client()?.get(&url).send()?.error_for_status()?.text()?;
+        let mut releases = parse_index(&content, &url)?;
+        for asset in releases.iter_mut().flat_map(|r| r.assets.iter_mut()) {
+            if !asset.url.contains("://") {
+                asset.url = format!("{}/{}", self.base, asset.url.trim_start_matches('/'));
+            }
+        }
+        Ok(releases)
+    }
+
+    fn download(&self, asset: &ReleaseAsset, dest: &mut dyn Write, progress: Progress) -> Result<(), UpdateError> {
+        http_download(http_client()?.get(&asset.url), dest, progress)
+    }
+}
+
+struct LocalSource {
+    path: PathBuf,
+}
+
+impl ReleaseSource for LocalSource {
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn releases(&self) -> Result<Vec<Release>, UpdateError> {
+        if self.path.is_file() {
+            return Ok(tarball_release(&self.path).into_iter().collect());
+        }
+
+        let index = self.path.join(INDEX_FILE);
+        if index.is_file() {
+            let mut releases = parse_index(&std::fs::read_to_string(&index)?, &index.display().to_string())?;
+            for asset in releases.iter_mut().flat_map(|r| r.assets.iter_mut()) {
+                asset.url = self.path.join(&asset.url).display().to_string();
+            }
+            return Ok(releases);
+        }
+
+        let mut releases = Vec::new();
+        for entry in std::fs::read_dir(&self.path)? {
+            releases.extend(tarball_release(&entry?.path()));
+        }
+        Ok(releases)
+    }
+
+    fn download(&self, asset: &ReleaseAsset, dest: &mut dyn Write, progress: Progress) -> Result<(), UpdateError> {
+        let file = std::fs::File::open(&asset.url)?;
+        let total = file.metadata()?.len();
+        copy_with_progress(file, dest, Some(total), progress)
+    }
+}
+
+fn parse_index(content: &str, origin: &str) -> Result<Vec<Release>, UpdateError> {
+    serde_json::from_str::<ReleaseIndex>(content)
+        .map(|index| index.releases)
+        .map_err(|e| UpdateError::Index { origin: origin.to_string(), message: e.to_string() })
+}
+
+/// Versión de un tarball llamado `tfeeling-CLI-<versión>-<target>.tar.gz` para este sistema.
+fn tarball_version(name: &str, target: &str) -> Option<String> {
+    let version = name
+        .strip_prefix(BIN_NAME)?
+        .strip_prefix('-')?
+        .strip_suffix(".tar.gz")?
+        .strip_suffix(target)?
+        .strip_suffix('-')?;
+    parse_version(version).map(|v| v.to_string())
+}
+
+fn tarball_release(path: &Path) -> Option<Release> {
+    let name = path.file_name()?.to_str()?;
+    let version = tarball_version(name, self_update::get_target())?;
+    let date = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(|t| save::format_timestamp(t).chars().take(10).collect())
+        .unwrap_or_default();
+    let mut assets = vec![ReleaseAsset { name: name.to_string(), url: path.display().to_string() }];
+    // Las sumas (y su firma) del directorio valen para todos sus tarballs
+    for file in [verify::SUMS_FILE, verify::SIGNATURE_FILE] {
+        let sibling = path.with_file_name(file);
+        if sibling.is_file() {
+            assets.push(ReleaseAsset { name: file.to_string(), url: sibling.display().to_string() });
+        }
+    }
+    Some(Release { version, date, notes: String::new(), assets })
+}
+
+fn http_client() -> Result<reqwest::blocking::Client, UpdateError> {
+    Ok(reqwest::blocking::Client::builder()
+        .user_agent(concat!("tfeeling-CLI/", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
+
+fn http_download(request: reqwest::blocking::RequestBuilder, dest: &mut dyn Write, progress: Progress) -> Result<(), UpdateError> {
+    let response = request.send()?.error_for_status()?;
+    let total = response.content_length();
+    copy_with_progress(response, dest, total, progress)
+}
+
+fn copy_with_progress(mut src: impl Read, dest: &mut dyn Write, total: Option<u64>, progress: Progress) -> Result<(), UpdateError> {
+    let mut buf = [0u8; 64 * 1024];
+    let mut done = 0;
+    progress(0, total);
+    loop {
+        let n = src.read(&mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        dest.write_all(&buf[..n])?;
+        done += n as u64;
+        progress(done, total);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tarball_names() {
+        let target = "x86_64-unknown-linux-gnu";
+        assert_eq!(tarball_version("tfeeling-CLI-1.2.0-x86_64-unknown-linux-gnu.tar.gz", target).as_deref(), Some("1.2.0"));
+        assert_eq!(
+            tarball_version("tfeeling-CLI-v1.3.0-beta.1-x86_64-unknown-linux-gnu.tar.gz", target).as_deref(),
+            Some("1.3.0-beta.1")
+        );
+        // El nombre sin versión que publica el flujo de GitHub no sirve para un directorio
+        assert_eq!(tarball_version("tfeeling-CLI-x86_64-unknown-linux-gnu.tar.gz", target), None);
+        assert_eq!(tarball_version("tfeeling-CLI-1.2.0-aarch64-apple-darwin.tar.gz", target), None);
+    }
+
+    #[test]
+    fn test_local_directory() {
+        let dir = std::env::temp_dir().join("tfeeling_test_release");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let name = format!("tfeeling-CLI-1.4.0-{}.tar.gz", self_update::get_target());
+        std::fs::write(dir.join(&name), b"tarball").unwrap();
+        std::fs::write(dir.join("notas.txt"), b"?").unwrap();
+        std::fs::write(dir.join(verify::SUMS_FILE), b"").unwrap();
+
+        let source = SourceConfig::Local { path: dir.clone() }.open();
+        let releases = source.releases().unwrap();
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].version, "1.4.0");
+        assert!(releases[0].asset(verify::SUMS_FILE).is_some());
+
+        let asset = releases[0].asset_for(self_update::get_target()).unwrap();
+        let mut bytes = Vec::new();
+        let mut last = 0;
+        source.download(asset, &mut bytes, &mut |done, _| last = done).unwrap();
+        assert_eq!(bytes, b"tarball");
+        assert_eq!(last, 7);
+
+        // Con índice, las rutas son relativas al directorio
+        std::fs::write(
+            dir.join(INDEX_FILE),
+            r#"{ "releases": [{ "version": "1.5.0", "notes": "Arreglos", "assets": [{ "name": "x.tar.gz", "url": "1.5.0/x.tar.gz" }] }] }"#,
+        )
+        .unwrap();
+        let releases = source.releases().unwrap();
+        assert_eq!(releases[0].notes, "Arreglos");
+        assert_eq!(PathBuf::from(&releases[0].assets[0].url), dir.join("1.5.0/x.tar.gz"));
+    }
+}
@@ -0,0 +1,563 @@
+use crate::engine::GameState;
+use crate::i18n::tr;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MAX_NAME_LEN: usize = 32;
+
+/// Copias de seguridad que se conservan por partida (`<nombre>.1.bak` es la más reciente).
+pub const BACKUP_COUNT: usize = 3;
+
+/// Versión actual del formato de partida.
+pub const SAVE_VERSION: u32 = 4;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[i]` convierte una partida de la versión `i + 1` a la `i + 2`.
+const MIGRATIONS: [Migration; (SAVE_VERSION - 1) as usize] =
+    [migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4];
+
+/// v1: partidas sin `version` (la antigua `~/.sylvie_save.json`). Algunas no
+/// tenían `day`, y ninguna tenía enfriamientos, historial ni conversaciones.
+fn migrate_v1_to_v2(save: &mut Map<String, Value>) -> Result<(), String> {
+    save.entry("day").or_insert(Value::from(1));
+    save.entry("cooldowns").or_insert(Value::Object(Map::new()));
+    save.entry("recent_dialogues").or_insert(Value::Array(Vec::new()));
+    save.entry("used_once").or_insert(Value::Array(Vec::new()));
+    save.entry("conversation").or_insert(Value::Null);
+    Ok(())
+}
+
+/// v2: sin registro de acciones.
+fn migrate_v2_to_v3(save: &mut Map<String, Value>) -> Result<(), String> {
+    save.entry("log").or_insert(Value::Array(Vec::new()));
+    Ok(())
+}
+
+/// v3: el historial de repeticiones y las líneas `once` guardaban el texto
+/// traducido en lugar del id de la línea. No se puede traducir de vuelta, así
+/// que se vacían.
+fn migrate_v3_to_v4(save: &mut Map<String, Value>) -> Result<(), String> {
+    save.insert("recent_dialogues".to_string(), Value::Array(Vec::new()));
+    save.insert("used_once".to_string(), Value::Array(Vec::new()));
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    InvalidName(String),
+    AlreadyExists(String),
+    NotFound(String),
+    Io(io::Error),
+    Parse(serde_json::Error),
+    /// El campo `version` no es un número de versión válido.
+    InvalidVersion(Value),
+    /// La partida es de una versión más nueva que este ejecutable.
+    TooNew(u32),
+    Migration { from: u32, message: String },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::InvalidName(name) => write!(f, "{}", tr!("save.invalid_name", name = name, max = MAX_NAME_LEN)),
+            SaveError::AlreadyExists(name) => write!(f, "{}", tr!("save.already_exists", name = name)),
+            SaveError::NotFound(name) => write!(f, "{}", tr!("save.not_found", name = name)),
+            SaveError::Io(e) => write!(f, "{}", tr!("save.io", error = e)),
+            SaveError::Parse(e) => write!(f, "{}", tr!("save.parse", error = e)),
+            SaveError::InvalidVersion(v) => write!(f, "{}", tr!("save.invalid_version", version = v)),
+            SaveError::TooNew(v) => write!(f, "{}", tr!("save.too_new", version = v, supported = SAVE_VERSION)),
+            SaveError::Migration { from, message } => {
+                write!(f, "{}", tr!("save.migration", version = from, error = message))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(e: serde_json::Error) -> Self {
+        SaveError::Parse(e)
+    }
+}
+
+#[derive(Serialize)]
+struct SaveFile<'a> {
+    version: u32,
+    #[serde(flatten)]
+    state: &'a GameState,
+}
+
+/// Interpreta una partida aplicando las migraciones necesarias.
+/// Devuelve el estado y la versión original del archivo.
+pub fn parse_save(content: &str) -> Result<(GameState, u32), SaveError> {
+    let mut save: Map<String, Value> = serde_json::from_str(content)?;
+    let version = match save.remove("version") {
+        None => 1,
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= 1)
+            .ok_or(SaveError::InvalidVersion(v))?,
+    };
+    if version > SAVE_VERSION {
+        return Err(SaveError::TooNew(version));
+    }
+
+    for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        migrate(&mut save).map_err(|message| SaveError::Migration { from: from as u32 + 1, message })?;
+    }
+
+    let state = serde_json::from_value(Value::Object(save))
+        .map_err(|e| SaveError::Migration { from: version, message: e.to_string() })?;
+    Ok((state, version))
+}
+
+/// Lee una partida. Si era de una versión anterior, guarda una copia del
+/// original como `<nombre>.v<N>.bak` y la reescribe en el formato actual.
+pub fn read_state(path: &Path) -> Result<GameState, SaveError> {
+    let content = fs::read_to_string(path)?;
+    let (state, version) = parse_save(&content)?;
+    if version < SAVE_VERSION {
+        fs::copy(path, path.with_extension(format!("v{}.bak", version)))?;
+        write_state(path, &state)?;
+    }
+    Ok(state)
+}
+
+/// Partida cargada, indicando si hubo que recurrir a una copia de seguridad.
+pub struct Loaded {
+    pub state: GameState,
+    pub recovered_from: Option<PathBuf>,
+}
+
+/// Como `read_state`, pero si el archivo principal está dañado recupera la
+/// copia válida más reciente. El archivo dañado se conserva como `<nombre>.damaged`.
+pub fn load_with_recovery(path: &Path) -> Result<Loaded, SaveError> {
+    let err = match read_state(path) {
+        Ok(state) => return Ok(Loaded { state, recovered_from: None }),
+        Err(e @ (SaveError::Parse(_) | SaveError::InvalidVersion(_) | SaveError::Migration { .. })) => e,
+        Err(e) => return Err(e),
+    };
+
+    for backup in backup_paths(path) {
+        let Ok(content) = fs::read_to_string(&backup) else { continue };
+        if let Ok((state, _)) = parse_save(&content) {
+            fs::rename(path, path.with_extension("damaged"))?;
+            write_state(path, &state)?;
+            return Ok(Loaded { state, recovered_from: Some(backup) });
+        }
+    }
+    Err(err)
+}
+
+/// Rutas de las copias de seguridad, de la más reciente a la más antigua.
+pub fn backup_paths(path: &Path) -> Vec<PathBuf> {
+    (1..=BACKUP_COUNT).map(|i| path.with_extension(format!("{}.bak", i))).collect()
+}
+
+/// Guarda de forma atómica: escribe en un temporal, lo sincroniza a disco y lo
+/// renombra sobre el original, rotando antes las copias de seguridad. Si algo
+/// falla a mitad, la partida anterior queda intacta.
+pub fn write_state(path: &Path, state: &GameState) -> Result<(), SaveError> {
+    let content = serde_json::to_string_pretty(&SaveFile { version: SAVE_VERSION, state })?;
+
+    let tmp = path.with_extension("json.tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+
+    if path.exists() {
+        rotate_backups(path)?;
+    }
+    fs::rename(&tmp, path)?;
+
+    // Sincroniza el directorio para que el renombrado sobreviva a un corte (sólo Unix)
+    if let Some(dir) = path.parent()
+        && let Ok(dir) = File::open(dir)
+    {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+fn rotate_backups(path: &Path) -> Result<(), SaveError> {
+    let backups = backup_paths(path);
+    for i in (1..backups.len()).rev() {
+        if backups[i - 1].exists() {
+            fs::rename(&backups[i - 1], &backups[i])?;
+        }
+    }
+    fs::copy(path, &backups[0])?;
+    Ok(())
+}
+
+/// Resumen de una ranura para la pantalla de partidas.
+#[derive(Debug, Clone)]
+pub struct SlotInfo {
+    pub name: String,
+    pub day: u32,
+    pub affection: i32,
+    pub trust: i32,
+    pub modified: Option<SystemTime>,
+    /// Motivo por el que la partida no se puede abrir, si lo hay.
+    pub problem: Option<String>,
+}
+
+/// Directorio con una partida por archivo (`<nombre>.json`).
+pub struct SaveStore {
+    dir: PathBuf,
+}
+
+impl SaveStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn path(&self, name: &str) -> Result<PathBuf, SaveError> {
+        let valid = !name.is_empty()
+            && name.chars().count() <= MAX_NAME_LEN
+            && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(SaveError::InvalidName(name.to_string()));
+        }
+        Ok(self.dir.join(format!("{}.json", name)))
+    }
+
+    /// Lista las partidas, la más reciente primero. Las que no se pueden
+    /// leer aparecen con `problem` para que el jugador sepa qué pasa.
+    pub fn list(&self) -> Result<Vec<SlotInfo>, SaveError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut slots = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+            let parsed = fs::read_to_string(&path).map_err(SaveError::from).and_then(|c| parse_save(&c));
+            let (state, problem) = match parsed {
+                Ok((state, _)) => (state, None),
+                Err(e) => (GameState::default(), Some(e.to_string())),
+            };
+            slots.push(SlotInfo {
+                name: name.to_string(),
+                day: state.day,
+                affection: state.affection,
+                trust: state.trust,
+                modified: fs::metadata(&path).and_then(|m| m.modified()).ok(),
+                problem,
+            });
+        }
+        slots.sort_by(|a, b| b.modified.cmp(&a.modified).then_with(|| a.name.cmp(&b.name)));
+        Ok(slots)
+    }
+
+    /// Crea una partida nueva y devuelve su ruta.
+    pub fn create(&self, name: &str) -> Result<PathBuf, SaveError> {
+        let path = self.path(name)?;
+        if path.exists() {
+            return Err(SaveError::AlreadyExists(name.to_string()));
+        }
+        fs::create_dir_all(&self.dir)?;
+        write_state(&path, &GameState::default())?;
+        Ok(path)
+    }
+
+    /// Borra la partida junto con todo lo que la acompaña: copias de seguridad,
+    /// copias de migración (`.v<N>.bak`), el archivo dañado y temporales. Los
+    /// nombres de ranura no llevan puntos, así que `<nombre>.` sólo es de ella.
+    pub fn delete(&self, name: &str) -> Result<(), SaveError> {
+        let path = self.existing(name)?;
+        let prefix = format!("{}.", name);
+        for entry in fs::read_dir(&self.dir)? {
+            let sibling = entry?.path();
+            let owned = sibling.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(&prefix));
+            if owned && sibling != path {
+                fs::remove_file(sibling)?;
+            }
+        }
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    pub fn duplicate(&self, from: &str, to: &str) -> Result<PathBuf, SaveError> {
+        let source = self.existing(from)?;
+        let target = self.path(to)?;
+        if target.exists() {
+            return Err(SaveError::AlreadyExists(to.to_string()));
+        }
+        fs::copy(source, &target)?;
+        Ok(target)
+    }
+
+    /// Importa la antigua `~/.sylvie_save.json` como ranura "principal" si aún no hay
+    /// partidas. Después se renombra a `.sylvie_save.json.migrated`, para que no
+    /// vuelva a aparecer si se borran todas las ranuras.
+    pub fn import_legacy(&self, legacy: &Path) -> Result<(), SaveError> {
+        if !legacy.exists() || !self.list()?.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        fs::copy(legacy, self.path("principal")?)?;
+        fs::rename(legacy, migrated(legacy))?;
+        Ok(())
+    }
+
+    /// Trae las ranuras del antiguo directorio `~/.sylvie_saves` si aún no hay
+    /// partidas. Se copian con sus copias de seguridad y, si se copió todo, el
+    /// directorio antiguo se renombra a `.sylvie_saves.migrated` para no volver a
+    /// importarlas si se borran aquí. Nunca se borra nada.
+    pub fn import_legacy_dir(&self, legacy: &Path) -> Result<(), SaveError> {
+        if !legacy.is_dir() || !self.list()?.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        // Si un directorio está dentro del otro (p. ej. `--save-dir ~/.sylvie_saves/nuevo`)
+        // no se importa: renombrar el antiguo se llevaría también las partidas nuevas
+        let (old, new) = (legacy.canonicalize()?, self.dir.canonicalize()?);
+        if old.starts_with(&new) || new.starts_with(&old) {
+            return Ok(());
+        }
+
+        let mut complete = true;
+        for entry in fs::read_dir(legacy)? {
+            let entry = entry?;
+            let target = self.dir.join(entry.file_name());
+            if entry.file_type()?.is_file() && !target.exists() {
+                fs::copy(entry.path(), target)?;
+            } else {
+                complete = false;
+            }
+        }
+        if complete {
+            fs::rename(legacy, migrated(legacy))?;
+        }
+        Ok(())
+    }
+
+    fn existing(&self, name: &str) -> Result<PathBuf, SaveError> {
+        let path = self.path(name)?;
+        if !path.exists() {
+            return Err(SaveError::NotFound(name.to_string()));
+        }
+        Ok(path)
+    }
+}
+
+/// Nombre con el que se aparta un archivo o directorio antiguo ya importado.
+fn migrated(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".migrated");
+    path.with_file_name(name)
+}
+
+/// Formatea una fecha como `AAAA-MM-DD HH:MM` (UTC).
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let days = (secs / 86_400) as i64;
+    let (hour, minute) = ((secs % 86_400) / 3600, (secs % 3600) / 60);
+
+    // Conversión de días desde 1970 a fecha civil (algoritmo de H. Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, hour, minute)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn temp_store(name: &str) -> SaveStore {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        SaveStore::new(dir)
+    }
+
+    #[test]
+    fn test_slot_lifecycle() {
+        let store = temp_store("tfeeling_test_slots");
+        store.create("ana").unwrap();
+        assert!(matches!(store.create("ana"), Err(SaveError::AlreadyExists(_))));
+        assert!(matches!(store.create("../fuera"), Err(SaveError::InvalidName(_))));
+
+        let mut state = GameState { day: 4, affection: 12, ..Default::default() };
+        write_state(&store.path("ana").unwrap(), &state).unwrap();
+        store.duplicate("ana", "ana-copia").unwrap();
+        state.day = 9;
+        write_state(&store.path("ana").unwrap(), &state).unwrap();
+
+        let slots = store.list().unwrap();
+        assert_eq!(slots.len(), 2);
+        let copy = slots.iter().find(|s| s.name == "ana-copia").unwrap();
+        assert_eq!((copy.day, copy.affection), (4, 12));
+
+        store.delete("ana").unwrap();
+        assert!(matches!(store.delete("ana"), Err(SaveError::NotFound(_))));
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_import_legacy_file() {
+        let store = temp_store("tfeeling_test_legacy_file");
+        let legacy = std::env::temp_dir().join("tfeeling_test_legacy_file.json");
+        let _ = fs::remove_file(migrated(&legacy));
+        write_state(&legacy, &GameState { day: 6, ..Default::default() }).unwrap();
+
+        store.import_legacy(&legacy).unwrap();
+        assert_eq!(read_state(&store.path("principal").unwrap()).unwrap().day, 6);
+        assert!(!legacy.exists() && migrated(&legacy).exists());
+
+        // Borrar todas las ranuras no la trae de vuelta
+        store.delete("principal").unwrap();
+        store.import_legacy(&legacy).unwrap();
+        assert!(store.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_import_legacy_dir() {
+        let store = temp_store("tfeeling_test_legacy_dir");
+        let legacy = std::env::temp_dir().join("tfeeling_test_legacy_dir_old");
+        let _ = fs::remove_dir_all(&legacy);
+        fs::create_dir_all(&legacy).unwrap();
+        write_state(&legacy.join("ana.json"), &GameState { day: 3, ..Default::default() }).unwrap();
+        write_state(&legacy.join("ana.json"), &GameState { day: 4, ..Default::default() }).unwrap();
+
+        let _ = fs::remove_dir_all(migrated(&legacy));
+        fs::create_dir_all(legacy.join("otros")).unwrap();
+
+        // Lo que no se puede copiar (aquí un subdirectorio) deja el antiguo en su sitio
+        store.import_legacy_dir(&legacy).unwrap();
+        let slots = store.list().unwrap();
+        assert_eq!((slots.len(), slots[0].day), (1, 4));
+        assert!(store.dir.join("ana.1.bak").exists());
+        assert!(legacy.join("otros").exists() && legacy.join("ana.json").exists());
+
+        fs::remove_dir(legacy.join("otros")).unwrap();
+        fs::remove_dir_all(&store.dir).unwrap();
+        store.import_legacy_dir(&legacy).unwrap();
+        assert!(!legacy.exists() && migrated(&legacy).join("ana.json").exists());
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_import_legacy_dir_nested() {
+        let legacy = std::env::temp_dir().join("tfeeling_test_legacy_nested");
+        let _ = fs::remove_dir_all(&legacy);
+        fs::create_dir_all(&legacy).unwrap();
+        write_state(&legacy.join("ana.json"), &GameState::default()).unwrap();
+
+        // El directorio nuevo dentro del antiguo, y el mismo con otra ruta
+        for dir in [legacy.join("nuevo"), legacy.join("nuevo/..")] {
+            let store = SaveStore::new(dir);
+            store.import_legacy_dir(&legacy).unwrap();
+            assert!(legacy.join("ana.json").exists());
+            assert!(!legacy.join("nuevo/ana.json").exists());
+        }
+    }
+
+    #[test]
+    fn test_migrate_v1_save() {
+        let store = temp_store("tfeeling_test_migration");
+        fs::create_dir_all(&store.dir).unwrap();
+        let path = store.path("vieja").unwrap();
+        let legacy = r#"{
+            "affection": 25, "trust": 12, "name": "Sylvie", "last_action": "pat_head",
+            "time_of_day": "afternoon", "hour": 14, "minute": 0, "actions_taken": 7,
+            "daily_affection": 4, "daily_trust": 2, "last_dialogue": "..."
+        }"#;
+        fs::write(&path, legacy).unwrap();
+
+        let state = read_state(&path).unwrap();
+        assert_eq!((state.day, state.affection, state.trust), (1, 25, 12));
+        assert!(state.cooldowns.is_empty());
+        assert!(state.log.is_empty());
+
+        assert_eq!(fs::read_to_string(path.with_extension("v1.bak")).unwrap(), legacy);
+        let upgraded: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(upgraded["version"], SAVE_VERSION);
+    }
+
+    #[test]
+    fn test_migrate_v3_clears_dialogue_history() {
+        let v3 = r#"{
+            "version": 3, "affection": 25, "trust": 12, "name": "Sylvie", "last_action": "talk",
+            "time_of_day": "morning", "hour": 8, "minute": 0, "day": 2, "actions_taken": 0,
+            "daily_affection": 0, "daily_trust": 0, "last_dialogue": "Hola.", "cooldowns": {},
+            "recent_dialogues": ["Hola."], "used_once": ["Hola."], "conversation": null, "log": []
+        }"#;
+        let (state, version) = parse_save(v3).unwrap();
+        assert_eq!((version, state.day), (3, 2));
+        assert!(state.recent_dialogues.is_empty());
+        assert!(state.used_once.is_empty());
+    }
+
+    #[test]
+    fn test_backups_and_recovery() {
+        let store = temp_store("tfeeling_test_recovery");
+        let path = store.create("ana").unwrap();
+        for day in 2..=5 {
+            write_state(&path, &GameState { day, ..Default::default() }).unwrap();
+        }
+
+        let backups = backup_paths(&path);
+        assert_eq!(backups.len(), BACKUP_COUNT);
+        assert!(backups.iter().all(|b| b.exists()));
+        assert_eq!(read_state(&backups[0]).unwrap().day, 4);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        // Archivo principal a medio escribir: se recupera la copia más reciente
+        fs::write(&path, r#"{ "version": 2, "affec"#).unwrap();
+        let loaded = load_with_recovery(&path).unwrap();
+        assert_eq!(loaded.state.day, 4);
+        assert_eq!(loaded.recovered_from, Some(backups[0].clone()));
+        assert!(path.with_extension("damaged").exists());
+        assert_eq!(read_state(&path).unwrap().day, 4);
+
+        fs::write(path.with_extension("v2.bak"), "{}").unwrap();
+        store.delete("ana").unwrap();
+        assert!(backups.iter().all(|b| !b.exists()));
+        assert!(!path.with_extension("damaged").exists());
+        assert!(!path.with_extension("v2.bak").exists());
+    }
+
+    #[test]
+    fn test_newer_save_is_rejected() {
+        let content = format!(r#"{{ "version": {} }}"#, SAVE_VERSION + 1);
+        assert!(matches!(parse_save(&content), Err(SaveError::TooNew(_))));
+
+        let broken = r#"{ "version": 2, "affection": "mucho" }"#;
+        assert!(matches!(parse_save(broken), Err(SaveError::Migration { from: 2, .. })));
+        assert!(matches!(parse_save(r#"{ "version": "2" }"#), Err(SaveError::InvalidVersion(_))));
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        let t = UNIX_EPOCH + Duration::from_secs(1_792_332_180);
+        assert_eq!(format_timestamp(t), "2026-10-18 14:03");
+    }
+}
@@ -0,0 +1,95 @@
+use crate::condition::Condition;
+use crate::dialogue::{parse_json, DialogueLoadError};
+use crate::engine::GameState;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Una expresión de Sylvie y cuándo se muestra.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expression {
+    pub name: String,
+    /// Imagen relativa a `art/`.
+    pub file: String,
+    #[serde(default)]
+    pub when: Option<Condition>,
+}
+
+/// Contenido de `modules/art/sprites.json`. Gana la primera expresión
+/// cuya condición se cumpla; si ninguna, se usa `fallback`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpriteManifest {
+    pub fallback: String,
+    #[serde(default)]
+    pub expressions: Vec<Expression>,
+}
+
+impl SpriteManifest {
+    pub fn parse(content: &str, path: &Path) -> Result<Self, DialogueLoadError> {
+        parse_json(content, path)
+    }
+
+    /// Expresión que corresponde al estado actual, si alguna encaja.
+    pub fn expression(&self, state: &GameState) -> Option<&Expression> {
+        self.expressions
+            .iter()
+            .find(|e| e.when.as_ref().is_none_or(|w| w.evaluate(state)))
+    }
+
+    /// Nombre y archivo de la expresión actual (`fallback` si ninguna encaja).
+    pub fn select(&self, state: &GameState) -> (&str, &str) {
+        self.expression(state)
+            .map(|e| (e.name.as_str(), e.file.as_str()))
+            .unwrap_or(("fallback", &self.fallback))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_expression() {
+        let manifest = SpriteManifest::parse(r#"{
+            "fallback": "base.png",
+            "expressions": [
+                { "name": "sleepy", "file": "sleepy.png", "when": "time == \"night\" && hour >= 22" },
+                { "name": "scared", "file": "scared.png", "when": "affection < 20 || trust < 10" },
+                { "name": "blushing", "file": "blushing.png", "when": "last_action == \"pat_head\" && affection >= 60" },
+                { "name": "smiling", "file": "smiling.png", "when": "affection >= 60" }
+            ]
+        }"#, Path::new("sprites.json")).unwrap();
+
+        let mut state = GameState::default();
+        assert_eq!(manifest.select(&state), ("scared", "scared.png"));
+
+        state.affection = 70;
+        state.trust = 40;
+        state.last_action = "pat_head".to_string();
+        assert_eq!(manifest.select(&state), ("blushing", "blushing.png"));
+
+        state.last_action = "talk".to_string();
+        assert_eq!(manifest.select(&state), ("smiling", "smiling.png"));
+
+        state.time_of_day = "night".to_string();
+        state.hour = 22;
+        assert_eq!(manifest.select(&state), ("sleepy", "sleepy.png"));
+
+        state.hour = 20;
+        state.affection = 30;
+        assert_eq!(manifest.select(&state), ("fallback", "base.png"));
+    }
+
+    #[test]
+    fn test_bundled_manifest_files_exist() {
+        let content = crate::assets::embedded("art/sprites.json").unwrap();
+        let manifest = SpriteManifest::parse(std::str::from_utf8(content).unwrap(), Path::new("sprites.json")).unwrap();
+        let files = std::iter::once(&manifest.fallback).chain(manifest.expressions.iter().map(|e| &e.file));
+        for file in files {
+            let bytes = crate::assets::embedded(&format!("art/{}", file));
+            assert!(bytes.is_some(), "art/{} no está embebido", file);
+            assert!(image::load_from_memory(bytes.unwrap()).is_ok(), "art/{} no es una imagen válida", file);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Revelado progresivo del diálogo, con ritmo independiente del teclado.
+
+use crate::i18n::tr;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Velocidad del texto.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextSpeed {
+    Slow,
+    Normal,
+    Fast,
+    Instant,
+}
+
+impl TextSpeed {
+    /// Pausa entre caracteres; `None` muestra el texto de golpe.
+    fn char_delay(self) -> Option<Duration> {
+        match self {
+            TextSpeed::Slow => Some(Duration::from_millis(60)),
+            TextSpeed::Normal => Some(Duration::from_millis(30)),
+            TextSpeed::Fast => Some(Duration::from_millis(10)),
+            TextSpeed::Instant => None,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            TextSpeed::Slow => TextSpeed::Normal,
+            TextSpeed::Normal => TextSpeed::Fast,
+            TextSpeed::Fast => TextSpeed::Instant,
+            TextSpeed::Instant => TextSpeed::Slow,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TextSpeed::Slow => tr!("speed.slow"),
+            TextSpeed::Normal => tr!("speed.normal"),
+            TextSpeed::Fast => tr!("speed.fast"),
+            TextSpeed::Instant => tr!("speed.instant"),
+        }
+    }
+}
+
+/// Cuántas pausas normales dura la pausa tras un signo de puntuación.
+fn pause_factor(c: char) -> u32 {
+    match c {
+        '.' | '!' | '?' | '…' => 8,
+        ',' | ';' | ':' => 4,
+        _ => 1,
+    }
+}
+
+pub struct Typewriter {
+    pub speed: TextSpeed,
+    /// Tiempo que el texto completo queda en pantalla antes de borrarse solo.
+    pub auto_advance: Option<Duration>,
+    visible: usize,
+    next_at: Instant,
+    finished_at: Option<Instant>,
+}
+
+impl Typewriter {
+    pub fn new(speed: TextSpeed, auto_advance: Option<Duration>) -> Self {
+        Self { speed, auto_advance, visible: 0, next_at: Instant::now(), finished_at: None }
+    }
+
+    pub fn visible(&self) -> usize {
+        self.visible
+    }
+
+    /// Empieza a revelar un texto nuevo.
+    pub fn restart(&mut self, now: Instant) {
+        self.visible = 0;
+        self.next_at = now;
+        self.finished_at = None;
+    }
+
+    /// Muestra el texto completo de inmediato.
+    pub fn skip(&mut self, text: &str, now: Instant) {
+        self.visible = text.chars().count();
+        self.finished_at = Some(now);
+    }
+
+    pub fn is_done(&self, text: &str) -> bool {
+        self.visible >= text.chars().count()
+    }
+
+    /// Avanza según el tiempo transcurrido y devuelve los caracteres recién revelados.
+    pub fn tick(&mut self, text: &str, now: Instant) -> Vec<char> {
+        let total = text.chars().count();
+        let Some(delay) = self.speed.char_delay() else {
+            if self.visible < total {
+                self.skip(text, now);
+            }
+            return Vec::new();
+        };
+
+        let mut revealed = Vec::new();
+        while self.visible < total && self.next_at <= now {
+            let c = text.chars().nth(self.visible).unwrap_or(' ');
+            self.visible += 1;
+            self.next_at += delay * pause_factor(c);
+            revealed.push(c);
+        }
+        if self.visible >= total && self.finished_at.is_none() {
+            self.finished_at = Some(now);
+        }
+        revealed
+    }
+
+    /// Si el texto completo ya lleva en pantalla el tiempo de avance automático.
+    pub fn should_advance(&self, now: Instant) -> bool {
+        match (self.auto_advance, self.finished_at) {
+            (Some(wait), Some(done)) => now.duration_since(done) >= wait,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reveal_follows_time() {
+        let start = Instant::now();
+        let mut tw = Typewriter::new(TextSpeed::Normal, Some(Duration::from_secs(2)));
+        tw.restart(start);
+
+        // "Hola." : la H sale enseguida; el punto añade una pausa larga
+        assert_eq!(tw.tick("Hola. Sí", start), vec!['H']);
+        assert_eq!(tw.tick("Hola. Sí", start + Duration::from_millis(90)), vec!['o', 'l', 'a']);
+        assert_eq!(tw.tick("Hola. Sí", start + Duration::from_millis(130)), vec!['.']);
+        assert!(tw.tick("Hola. Sí", start + Duration::from_millis(300)).is_empty());
+        assert_eq!(tw.tick("Hola. Sí", start + Duration::from_millis(450)), vec![' ', 'S', 'í']);
+        assert!(tw.is_done("Hola. Sí"));
+
+        assert!(!tw.should_advance(start + Duration::from_secs(1)));
+        assert!(tw.should_advance(start + Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_instant_speed() {
+        let now = Instant::now();
+        let mut tw = Typewriter::new(TextSpeed::Instant, None);
+        tw.restart(now);
+        assert!(tw.tick("Buenos días", now).is_empty());
+        assert_eq!(tw.visible(), 11);
+        assert!(!tw.should_advance(now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_punctuation_pauses_and_skip() {
+        assert_eq!(pause_factor('a'), 1);
+        assert_eq!(pause_factor(','), 4);
+        assert!(pause_factor('.') > pause_factor(','));
+        assert_eq!(pause_factor('…'), pause_factor('?'));
+
+        // Tras saltar la animación no se revela (ni suena) nada más
+        let start = Instant::now();
+        let mut tw = Typewriter::new(TextSpeed::Slow, None);
+        tw.restart(start);
+        assert_eq!(tw.tick("Hola", start), vec!['H']);
+        tw.skip("Hola", start);
+        assert!(tw.is_done("Hola"));
+        assert!(tw.tick("Hola", start + Duration::from_secs(1)).is_empty());
+    }
+}
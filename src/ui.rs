@@ -1,14 +1,26 @@
+use crate::assets;
+use crate::audio::Audio;
+use crate::config::{Config, ConfigError, SessionConfig, UpdateConfig};
 use crate::engine::Engine;
+use crate::i18n::{self, tr};
+use crate::paths::Paths;
+use crate::release::Release;
+use crate::save::{self, SaveStore, SlotInfo};
+use crate::typewriter::Typewriter;
+use crate::update::{self, InstallEvent, VersionCheck};
 use crossterm::event::{self, Event, KeyCode};
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Layout},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Paragraph, Wrap, List, ListItem, ListState},
+    text::Line,
+    widgets::{Block, Borders, Gauge, Paragraph, Wrap, List, ListItem, ListState},
     Terminal,
 };
 use std::error::Error;
-use std::time::Duration;
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
 use ratatui_image::{
     picker::Picker,
     protocol::StatefulProtocol,
@@ -16,101 +28,787 @@ use ratatui_image::{
     Resize,
 };
 
-pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut engine: Engine) -> Result<(), Box<dyn Error>> 
+/// Entrada del menú principal: las acciones del registro seguidas de las opciones fijas,
+/// o las respuestas posibles si hay una conversación abierta.
+enum MenuEntry {
+    Action { id: String, label: String },
+    Reply { index: usize, label: String },
+    Saves,
+    Settings,
+    Credits,
+    CheckVersion,
+    Quit,
+}
+
+impl MenuEntry {
+    fn label(&self) -> &str {
+        match self {
+            MenuEntry::Action { label, .. } | MenuEntry::Reply { label, .. } => label,
+            MenuEntry::Saves => tr!("menu.saves"),
+            MenuEntry::Settings => tr!("menu.settings"),
+            MenuEntry::Credits => tr!("menu.credits"),
+            MenuEntry::CheckVersion => tr!("menu.check_version"),
+            MenuEntry::Quit => tr!("menu.quit"),
+        }
+    }
+}
+
+fn menu_entries(engine: &Engine) -> Vec<MenuEntry> {
+    if engine.state.conversation.is_some() {
+        return engine
+            .replies()
+            .iter()
+            .enumerate()
+            .map(|(index, c)| MenuEntry::Reply { index, label: c.label.clone() })
+            .collect();
+    }
+
+    let mut entries: Vec<MenuEntry> = engine
+        .actions
+        .iter()
+        .map(|a| MenuEntry::Action { id: a.id.clone(), label: a.label.clone() })
+        .collect();
+    entries.extend([MenuEntry::Saves, MenuEntry::Settings, MenuEntry::Credits, MenuEntry::CheckVersion, MenuEntry::Quit]);
+    entries
+}
+
+#[derive(PartialEq, Eq)]
+enum Screen {
+    Slots,
+    Game,
+    Settings,
+    Changelog,
+}
+
+/// Colores de la interfaz según la configuración.
+#[derive(Clone, Copy)]
+struct Theme {
+    status: Color,
+    portrait: Color,
+    text: Color,
+    frame: Color,
+    error: Color,
+    dim: Style,
+    highlight: Style,
+}
+
+impl Theme {
+    fn new(config: &Config) -> Self {
+        if config.accessibility.high_contrast {
+            Self {
+                status: Color::White,
+                portrait: Color::Yellow,
+                text: Color::White,
+                frame: Color::White,
+                error: Color::LightRed,
+                dim: Style::default().fg(Color::Gray),
+                highlight: Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD),
+            }
+        } else if !config.display.colors {
+            Self {
+                status: Color::Reset,
+                portrait: Color::Reset,
+                text: Color::Reset,
+                frame: Color::Reset,
+                error: Color::Reset,
+                dim: Style::default().add_modifier(Modifier::DIM),
+                highlight: Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            }
+        } else {
+            Self {
+                status: Color::Cyan,
+                portrait: Color::Magenta,
+                text: Color::White,
+                frame: Color::Gray,
+                error: Color::Red,
+                dim: Style::default().fg(Color::DarkGray),
+                highlight: Style::default().fg(Color::Black).bg(Color::Magenta).add_modifier(Modifier::BOLD),
+            }
+        }
+    }
+}
+
+/// Preferencias del jugador y los subsistemas que dependen de ellas.
+struct Session {
+    /// Configuración en uso: la del archivo más las opciones de la línea de órdenes.
+    config: Config,
+    /// Lo que hay en `config.toml`; al guardar sólo se le añaden los ajustes cambiados.
+    saved: Config,
+    /// Ajustes que el jugador ha cambiado en esta sesión.
+    changed: Vec<Setting>,
+    audio: Audio,
+    typewriter: Typewriter,
+    theme: Theme,
+    /// Aviso de la búsqueda de actualizaciones en segundo plano.
+    notification: Option<String>,
+}
+
+impl Session {
+    fn new(config: SessionConfig, audio: Audio) -> Self {
+        let SessionConfig { saved, effective: config } = config;
+        let mut session = Self {
+            typewriter: Typewriter::new(config.text_speed(), None),
+            theme: Theme::new(&config),
+            config,
+            saved,
+            changed: Vec::new(),
+            audio,
+            notification: None,
+        };
+        session.apply();
+        session
+    }
+
+    /// Traslada la configuración al sonido, al texto y a los colores.
+    fn apply(&mut self) {
+        self.audio.configure(&self.config.audio);
+        self.typewriter.speed = self.config.text_speed();
+        self.typewriter.auto_advance = match self.config.text.auto_advance {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        };
+        self.theme = Theme::new(&self.config);
+    }
+
+    /// Cambia un ajuste desde la pantalla de ajustes o un atajo de teclado.
+    fn change(&mut self, setting: Setting, forward: bool) {
+        setting.change(&mut self.config, forward);
+        if !self.changed.contains(&setting) {
+            self.changed.push(setting);
+        }
+        // El aviso de actualización se buscó con el canal anterior
+        if setting == Setting::UpdateChannel {
+            self.notification = None;
+        }
+        self.apply();
+    }
+
+    /// Guarda en `config.toml` los ajustes cambiados, sin las opciones de una sola sesión.
+    fn save(&mut self, path: &Path) -> Result<(), ConfigError> {
+        let saved = settings_to_save(&self.saved, &self.config, &self.changed);
+        saved.save(path)?;
+        self.saved = saved;
+        Ok(())
+    }
+}
+
+/// `saved` con los ajustes de `changed` tomados de `config`.
+fn settings_to_save(saved: &Config, config: &Config, changed: &[Setting]) -> Config {
+    let mut result = saved.clone();
+    for setting in changed {
+        setting.copy(config, &mut result);
+    }
+    result
+}
+
+/// Opciones editables en la pantalla de ajustes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    Portrait,
+    Colors,
+    Volume,
+    Muted,
+    Voice,
+    TextSpeed,
+    AutoAdvance,
+    UpdateCheck,
+    UpdateChannel,
+    HighContrast,
+    ReduceMotion,
+}
+
+const SETTINGS: [Setting; 11] = [
+    Setting::Portrait,
+    Setting::Colors,
+    Setting::Volume,
+    Setting::Muted,
+    Setting::Voice,
+    Setting::TextSpeed,
+    Setting::AutoAdvance,
+    Setting::UpdateCheck,
+    Setting::UpdateChannel,
+    Setting::HighContrast,
+    Setting::ReduceMotion,
+];
+
+/// Segundos de avance automático que se pueden elegir (0 = desactivado).
+const AUTO_ADVANCE_STEPS: [u64; 5] = [0, 2, 3, 5, 8];
+
+impl Setting {
+    fn label(self) -> &'static str {
+        match self {
+            Setting::Portrait => tr!("settings.portrait"),
+            Setting::Colors => tr!("settings.colors"),
+            Setting::Volume => tr!("settings.volume"),
+            Setting::Muted => tr!("settings.muted"),
+            Setting::Voice => tr!("settings.voice"),
+            Setting::TextSpeed => tr!("settings.text_speed"),
+            Setting::AutoAdvance => tr!("settings.auto_advance"),
+            Setting::UpdateCheck => tr!("settings.update_check"),
+            Setting::UpdateChannel => tr!("settings.update_channel"),
+            Setting::HighContrast => tr!("settings.high_contrast"),
+            Setting::ReduceMotion => tr!("settings.reduce_motion"),
+        }
+    }
+
+    fn value(self, config: &Config) -> String {
+        let yes_no = |b: bool| if b { tr!("common.yes") } else { tr!("common.no") }.to_string();
+        match self {
+            Setting::Portrait => yes_no(config.display.show_portrait),
+            Setting::Colors => yes_no(config.display.colors),
+            Setting::Volume => format!("{:.0}%", config.audio.volume * 100.0),
+            Setting::Muted => yes_no(config.audio.muted),
+            Setting::Voice => yes_no(config.audio.voice),
+            Setting::TextSpeed => config.text.speed.label().to_string(),
+            Setting::AutoAdvance => match config.text.auto_advance {
+                0 => tr!("common.no").to_string(),
+                secs => format!("{} s", secs),
+            },
+            Setting::UpdateCheck => yes_no(config.updates.check_on_startup),
+            Setting::UpdateChannel => config.updates.channel.label().to_string(),
+            Setting::HighContrast => yes_no(config.accessibility.high_contrast),
+            Setting::ReduceMotion => yes_no(config.accessibility.reduce_motion),
+        }
+    }
+
+    /// Pasa al valor siguiente (o al anterior si `forward` es falso).
+    fn change(self, config: &mut Config, forward: bool) {
+        match self {
+            Setting::Portrait => config.display.show_portrait = !config.display.show_portrait,
+            Setting::Colors => config.display.colors = !config.display.colors,
+            Setting::Volume => config.audio.volume = step_volume(config.audio.volume, if forward { 0.1 } else { -0.1 }),
+            Setting::Muted => config.audio.muted = !config.audio.muted,
+            Setting::Voice => config.audio.voice = !config.audio.voice,
+            Setting::TextSpeed => {
+                config.text.speed = if forward {
+                    config.text.speed.next()
+                } else {
+                    // Tres pasos adelante equivalen a uno atrás
+                    config.text.speed.next().next().next()
+                }
+            }
+            Setting::AutoAdvance => {
+                let i = AUTO_ADVANCE_STEPS.iter().position(|s| *s == config.text.auto_advance).unwrap_or(0);
+                let n = AUTO_ADVANCE_STEPS.len();
+                config.text.auto_advance = AUTO_ADVANCE_STEPS[if forward { (i + 1) % n } else { (i + n - 1) % n }];
+            }
+            Setting::UpdateCheck => config.updates.check_on_startup = !config.updates.check_on_startup,
+            Setting::UpdateChannel => config.updates.channel = config.updates.channel.toggle(),
+            Setting::HighContrast => config.accessibility.high_contrast = !config.accessibility.high_contrast,
+            Setting::ReduceMotion => config.accessibility.reduce_motion = !config.accessibility.reduce_motion,
+        }
+    }
+
+    /// Copia el valor de este ajuste de `from` a `to`.
+    fn copy(self, from: &Config, to: &mut Config) {
+        match self {
+            Setting::Portrait => to.display.show_portrait = from.display.show_portrait,
+            Setting::Colors => to.display.colors = from.display.colors,
+            Setting::Volume => to.audio.volume = from.audio.volume,
+            Setting::Muted => to.audio.muted = from.audio.muted,
+            Setting::Voice => to.audio.voice = from.audio.voice,
+            Setting::TextSpeed => to.text.speed = from.text.speed,
+            Setting::AutoAdvance => to.text.auto_advance = from.text.auto_advance,
+            Setting::UpdateCheck => to.updates.check_on_startup = from.updates.check_on_startup,
+            Setting::UpdateChannel => to.updates.channel = from.updates.channel,
+            Setting::HighContrast => to.accessibility.high_contrast = from.accessibility.high_contrast,
+            Setting::ReduceMotion => to.accessibility.reduce_motion = from.accessibility.reduce_motion,
+        }
+    }
+}
+
+/// Sube o baja el volumen en pasos de una décima, dentro de 0.0..=1.0.
+fn step_volume(volume: f32, delta: f32) -> f32 {
+    ((volume + delta) * 10.0).round().clamp(0.0, 10.0) / 10.0
+}
+
+struct SettingsScreen {
+    list_state: ListState,
+    message: String,
+}
+
+impl SettingsScreen {
+    fn new() -> Self {
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+        Self { list_state, message: String::new() }
+    }
+
+    /// Devuelve `true` cuando el jugador sale de la pantalla.
+    fn handle_key(&mut self, code: KeyCode, session: &mut Session) -> bool {
+        let i = self.list_state.selected().unwrap_or(0);
+        match code {
+            KeyCode::Up => self.list_state.select(Some(if i == 0 { SETTINGS.len() - 1 } else { i - 1 })),
+            KeyCode::Down => self.list_state.select(Some((i + 1) % SETTINGS.len())),
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char(' ') => session.change(SETTINGS[i], true),
+            KeyCode::Left => session.change(SETTINGS[i], false),
+            KeyCode::Esc | KeyCode::Char('q') => return true,
+            _ => {}
+        }
+        false
+    }
+}
+
+/// Novedades de las versiones pendientes, con la opción de instalar la más reciente.
+struct ChangelogScreen {
+    releases: Vec<Release>,
+    lines: Vec<Line<'static>>,
+    scroll: u16,
+    install: Option<Receiver<InstallEvent>>,
+    progress: (u64, Option<u64>),
+    installed: bool,
+    message: String,
+}
+
+impl ChangelogScreen {
+    fn new(releases: Vec<Release>) -> Self {
+        let mut lines = Vec::new();
+        for release in &releases {
+            let date = if release.date.is_empty() { String::new() } else { format!(" · {}", release.date) };
+            lines.push(Line::from(format!("v{}{}", release.version, date)).style(Style::default().add_modifier(Modifier::BOLD)));
+            if release.notes.trim().is_empty() {
+                lines.push(Line::from(tr!("changelog.no_notes")));
+            }
+            lines.extend(release.notes.trim().lines().map(|l| Line::from(l.to_string())));
+            lines.push(Line::default());
+        }
+        Self { releases, lines, scroll: 0, install: None, progress: (0, None), installed: false, message: String::new() }
+    }
+
+    /// Recoge el progreso de la instalación en curso.
+    fn poll(&mut self) {
+        let Some(rx) = &self.install else { return };
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                InstallEvent::Progress(done, total) => self.progress = (done, total),
+                InstallEvent::Done(result) => {
+                    self.installed = result.is_ok();
+                    self.message = match result {
+                        Ok(true) => tr!("changelog.installed", version = self.releases[0].version),
+                        Ok(false) => format!(
+                            "{} {}",
+                            tr!("changelog.installed", version = self.releases[0].version),
+                            update::unverified_warning(&self.releases[0].version)
+                        ),
+                        Err(e) => format!("{}: {}", tr!("update.error"), e),
+                    };
+                    self.install = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Devuelve `true` cuando el jugador sale de la pantalla.
+    fn handle_key(&mut self, code: KeyCode, config: &UpdateConfig) -> bool {
+        let last = self.lines.len().saturating_sub(1) as u16;
+        match code {
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll = (self.scroll + 1).min(last),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll = (self.scroll + 10).min(last),
+            KeyCode::Char('u') | KeyCode::Enter if self.install.is_none() && self.message.is_empty() => {
+                self.progress = (0, None);
+                self.install = Some(update::install_in_background(config.clone(), self.releases[0].clone()));
+            }
+            // La descarga no se interrumpe a medias
+            KeyCode::Esc | KeyCode::Char('q') => return self.install.is_none(),
+            _ => {}
+        }
+        false
+    }
+}
+
+/// Entrada de texto o confirmación pendiente en la pantalla de partidas.
+enum Prompt {
+    NewGame(String),
+    Duplicate { from: String, name: String },
+    ConfirmDelete(String),
+}
+
+enum SlotOutcome {
+    Stay,
+    Play,
+    Quit,
+}
+
+/// Pantalla de partidas: la primera fila es "Nueva partida" y el resto, las ranuras.
+struct SlotScreen {
+    slots: Vec<SlotInfo>,
+    list_state: ListState,
+    prompt: Option<Prompt>,
+    message: String,
+}
+
+impl SlotScreen {
+    fn new(store: &SaveStore) -> Self {
+        let mut screen = Self {
+            slots: Vec::new(),
+            list_state: ListState::default(),
+            prompt: None,
+            message: String::new(),
+        };
+        screen.refresh(store);
+        screen
+    }
+
+    fn refresh(&mut self, store: &SaveStore) {
+        match store.list() {
+            Ok(slots) => self.slots = slots,
+            Err(e) => self.message = e.to_string(),
+        }
+        let last = self.slots.len();
+        let selected = self.list_state.selected().unwrap_or(if last > 0 { 1 } else { 0 });
+        self.list_state.select(Some(selected.min(last)));
+    }
+
+    fn selected_slot(&self) -> Option<&SlotInfo> {
+        self.list_state.selected().and_then(|i| i.checked_sub(1)).and_then(|i| self.slots.get(i))
+    }
+
+    fn handle_key(&mut self, code: KeyCode, store: &SaveStore, engine: &mut Engine) -> SlotOutcome {
+        if let Some(prompt) = self.prompt.take() {
+            return self.handle_prompt(prompt, code, store, engine);
+        }
+
+        match code {
+            KeyCode::Up => {
+                let i = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(if i == 0 { self.slots.len() } else { i - 1 }));
+            }
+            KeyCode::Down => {
+                let i = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(if i >= self.slots.len() { 0 } else { i + 1 }));
+            }
+            KeyCode::Char('n') => self.prompt = Some(Prompt::NewGame(String::new())),
+            KeyCode::Char('d') => {
+                if let Some(slot) = self.selected_slot() {
+                    self.prompt = Some(Prompt::ConfirmDelete(slot.name.clone()));
+                }
+            }
+            KeyCode::Char('c') => {
+                if let Some(slot) = self.selected_slot() {
+                    self.prompt = Some(Prompt::Duplicate { from: slot.name.clone(), name: tr!("saves.copy_name", name = slot.name) });
+                }
+            }
+            KeyCode::Enter => match self.selected_slot() {
+                None => self.prompt = Some(Prompt::NewGame(String::new())),
+                Some(slot) => {
+                    let name = slot.name.clone();
+                    return self.open(store, engine, &name);
+                }
+            },
+            KeyCode::Esc | KeyCode::Char('q') => {
+                // Con una partida abierta, Esc vuelve al juego
+                return if engine.save_path().is_some() { SlotOutcome::Play } else { SlotOutcome::Quit };
+            }
+            _ => {}
+        }
+        SlotOutcome::Stay
+    }
+
+    fn handle_prompt(&mut self, prompt: Prompt, code: KeyCode, store: &SaveStore, engine: &mut Engine) -> SlotOutcome {
+        match (prompt, code) {
+            (Prompt::ConfirmDelete(name), KeyCode::Char('s') | KeyCode::Char('y')) => {
+                let deleted = store.path(&name).ok();
+                match store.delete(&name) {
+                    Ok(()) => {
+                        if engine.save_path().map(|p| p.to_path_buf()) == deleted {
+                            engine.close_save();
+                        }
+                        self.message = tr!("saves.deleted", name = name);
+                    }
+                    Err(e) => self.message = e.to_string(),
+                }
+                self.refresh(store);
+            }
+            (Prompt::ConfirmDelete(_), _) | (_, KeyCode::Esc) => {}
+            (Prompt::NewGame(name), KeyCode::Enter) => match store.create(&name) {
+                Ok(_) => return self.open(store, engine, &name),
+                Err(e) => {
+                    self.message = e.to_string();
+                    self.prompt = Some(Prompt::NewGame(name));
+                }
+            },
+            (Prompt::Duplicate { from, name }, KeyCode::Enter) => {
+                match store.duplicate(&from, &name) {
+                    Ok(_) => self.message = tr!("saves.duplicated", from = from, name = name),
+                    Err(e) => {
+                        self.message = e.to_string();
+                        self.prompt = Some(Prompt::Duplicate { from, name });
+                    }
+                }
+                self.refresh(store);
+            }
+            (Prompt::NewGame(mut name), code) => {
+                edit_name(&mut name, code);
+                self.prompt = Some(Prompt::NewGame(name));
+            }
+            (Prompt::Duplicate { from, mut name }, code) => {
+                edit_name(&mut name, code);
+                self.prompt = Some(Prompt::Duplicate { from, name });
+            }
+        }
+        SlotOutcome::Stay
+    }
+
+    fn open(&mut self, store: &SaveStore, engine: &mut Engine, name: &str) -> SlotOutcome {
+        let result = store.path(name).map_err(|e| e.to_string()).and_then(|path| engine.open_save(path).map_err(|e| e.to_string()));
+        match result {
+            Ok(recovered_from) => {
+                self.message.clear();
+                if let Some(backup) = recovered_from {
+                    engine.state.last_dialogue = tr!("saves.recovered", name = name, backup = backup.display());
+                }
+                SlotOutcome::Play
+            }
+            Err(e) => {
+                self.message = e;
+                self.refresh(store);
+                SlotOutcome::Stay
+            }
+        }
+    }
+}
+
+fn edit_name(name: &mut String, code: KeyCode) {
+    match code {
+        KeyCode::Backspace => {
+            name.pop();
+        }
+        KeyCode::Char(c) if !c.is_control() => name.push(c),
+        _ => {}
+    }
+}
+
+pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut engine: Engine, audio: Audio, config: SessionConfig, update_notice: Option<Receiver<VersionCheck>>, store: SaveStore, paths: &Paths) -> Result<(), Box<dyn Error>> 
 where 
     <B as Backend>::Error: 'static 
 {
     let mut menu_state = ListState::default();
     menu_state.select(Some(0));
 
-    let mut visible_chars = 0;
-
-    // Load image
-    let image_path = "modules/art/Sylvie-base.png";
-    let dyn_image = image::open(image_path)?;
+    // La expresión se elige cada vuelta; la imagen solo se recarga cuando cambia
     let picker = Picker::from_query_stdio().unwrap_or_else(|_| Picker::halfblocks());
-    let mut image_state = picker.new_resize_protocol(dyn_image);
+    let (name, file) = engine.sprites.select(&engine.state);
+    let mut expression = name.to_string();
+    let mut image_state = picker.new_resize_protocol(load_sprite(paths, &engine, file)?);
+
+    let mut session = Session::new(config, audio);
+    let mut screen = Screen::Slots;
+    let mut slots = SlotScreen::new(&store);
+    let mut settings = SettingsScreen::new();
+    let mut changelog = ChangelogScreen::new(Vec::new());
+    // Búsqueda en curso lanzada desde "Comprobar versión"
+    let mut version_check: Option<Receiver<(VersionCheck, Vec<Release>)>> = None;
 
     loop {
-        terminal.draw(|f| ui(f, &engine, &mut menu_state, visible_chars, &mut image_state))?;
-
-        if event::poll(Duration::from_millis(30))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
-                    KeyCode::Up => {
-                        let i = match menu_state.selected() {
-                            Some(i) => {
-                                if i == 0 {
-                                    5
-                                } else {
-                                    i - 1
-                                }
-                            }
-                            None => 0,
-                        };
-                        menu_state.select(Some(i));
+        if screen == Screen::Slots {
+            terminal.draw(|f| slots_ui(f, &mut slots, &session.theme))?;
+            if event::poll(Duration::from_millis(30))?
+                && let Event::Key(key) = event::read()?
+            {
+                match slots.handle_key(key.code, &store, &mut engine) {
+                    SlotOutcome::Stay => {}
+                    SlotOutcome::Play => {
+                        screen = Screen::Game;
+                        menu_state.select(Some(0));
+                        session.typewriter.restart(Instant::now());
                     }
-                    KeyCode::Down => {
-                        let i = match menu_state.selected() {
-                            Some(i) => {
-                                if i >= 5 {
-                                    0
-                                } else {
-                                    i + 1
+                    SlotOutcome::Quit => return Ok(()),
+                }
+            }
+            continue;
+        }
+
+        if screen == Screen::Settings {
+            terminal.draw(|f| settings_ui(f, &mut settings, &session))?;
+            if event::poll(Duration::from_millis(30))?
+                && let Event::Key(key) = event::read()?
+                && settings.handle_key(key.code, &mut session)
+            {
+                // Al salir se guarda; si falla, se avisa en el cuadro de diálogo
+                if let Err(e) = session.save(&paths.config_file) {
+                    engine.state.last_dialogue = tr!("settings.save_failed", error = e);
+                    session.typewriter.restart(Instant::now());
+                }
+                screen = Screen::Game;
+            }
+            continue;
+        }
+
+        if screen == Screen::Changelog {
+            changelog.poll();
+            terminal.draw(|f| changelog_ui(f, &mut changelog, &session.theme))?;
+            if event::poll(Duration::from_millis(30))?
+                && let Event::Key(key) = event::read()?
+                && changelog.handle_key(key.code, &session.config.updates)
+            {
+                // Sin instalar, queda el resumen de la comprobación
+                if !changelog.message.is_empty() {
+                    engine.state.last_dialogue = changelog.message.clone();
+                }
+                if changelog.installed {
+                    session.notification = None;
+                }
+                session.typewriter.restart(Instant::now());
+                screen = Screen::Game;
+            }
+            continue;
+        }
+
+        if let Some(notice) = update_notice.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            session.notification = Some(notice.to_string());
+        }
+        if let Some((check, pending)) = version_check.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            version_check = None;
+            engine.state.last_dialogue = check.to_string();
+            session.typewriter.restart(Instant::now());
+            if let VersionCheck::UpdateAvailable { .. } = check {
+                changelog = ChangelogScreen::new(pending);
+                screen = Screen::Changelog;
+                continue;
+            }
+        }
+        session.audio.set_phase(&engine.state.time_of_day);
+        let (name, file) = engine.sprites.select(&engine.state);
+        if name != expression {
+            expression = name.to_string();
+            image_state = picker.new_resize_protocol(load_sprite(paths, &engine, file)?);
+        }
+
+        // El texto avanza con el reloj, haya o no teclas pulsadas
+        let now = Instant::now();
+        let revealed = session.typewriter.tick(&engine.state.last_dialogue, now);
+        if let Some(c) = revealed.iter().rev().find(|c| c.is_alphanumeric()) {
+            session.audio.blip(*c, engine.mood());
+        }
+        if session.typewriter.should_advance(now)
+            && engine.state.conversation.is_none()
+            && !engine.state.last_dialogue.is_empty()
+            && engine.state.last_dialogue != "..."
+        {
+            engine.state.last_dialogue = "...".to_string();
+            session.typewriter.skip(&engine.state.last_dialogue, now);
+        }
+
+        let menu = menu_entries(&engine);
+        terminal.draw(|f| ui(f, &engine, &session, &menu, &mut menu_state, &mut image_state))?;
+
+        if event::poll(Duration::from_millis(15))?
+            && let Event::Key(key) = event::read()?
+        {
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                // Los atajos cambian los ajustes de la sesión; se guardan desde "Ajustes"
+                KeyCode::Char('m') => session.change(Setting::Muted, true),
+                KeyCode::Char('v') => session.change(Setting::Voice, true),
+                KeyCode::Char('t') => session.change(Setting::TextSpeed, true),
+                KeyCode::Char('+') | KeyCode::Char('-') => session.change(Setting::Volume, key.code == KeyCode::Char('+')),
+                KeyCode::Up => {
+                    let i = match menu_state.selected() {
+                        Some(i) => {
+                            if i == 0 {
+                                menu.len().saturating_sub(1)
+                            } else {
+                                i - 1
+                            }
+                        }
+                        None => 0,
+                    };
+                    menu_state.select(Some(i));
+                }
+                KeyCode::Down => {
+                    let i = match menu_state.selected() {
+                        Some(i) => {
+                            if i + 1 >= menu.len() {
+                                0
+                            } else {
+                                i + 1
+                            }
+                        }
+                        None => 0,
+                    };
+                    menu_state.select(Some(i));
+                }
+                KeyCode::Enter => {
+                    let in_conversation = engine.state.conversation.is_some();
+                    if !session.typewriter.is_done(&engine.state.last_dialogue) {
+                        // Skip animation
+                        session.typewriter.skip(&engine.state.last_dialogue, Instant::now());
+                        session.audio.stop_voice();
+                    } else if in_conversation {
+                        // Sylvie espera respuesta: el texto se mantiene visible
+                        if let Some(MenuEntry::Reply { index, .. }) = menu_state.selected().and_then(|i| menu.get(i))
+                            && let Err(e) = engine.reply(*index)
+                        {
+                            engine.state.last_dialogue = e.to_string();
+                        }
+                        menu_state.select(Some(0));
+                        session.typewriter.restart(Instant::now());
+                    } else if !engine.state.last_dialogue.is_empty() && engine.state.last_dialogue != "..." {
+                        // Dialogue is finished, clear it
+                        engine.state.last_dialogue = "...".to_string();
+                        session.typewriter.skip(&engine.state.last_dialogue, Instant::now());
+                    } else {
+                        // Dialogue is empty/cleared, perform action
+                        match menu_state.selected().and_then(|i| menu.get(i)) {
+                            Some(MenuEntry::Action { id, .. }) => {
+                                match engine.interact(id) {
+                                    Ok(()) => session.audio.effect(id),
+                                    Err(e) => engine.state.last_dialogue = e.to_string(),
+                                }
+                                if engine.state.conversation.is_some() {
+                                    menu_state.select(Some(0));
                                 }
                             }
-                            None => 0,
-                        };
-                        menu_state.select(Some(i));
-                    }
-                    KeyCode::Enter => {
-                        let dialogue_len = engine.state.last_dialogue.chars().count();
-                        if visible_chars < dialogue_len {
-                            // Skip animation
-                            visible_chars = dialogue_len;
-                        } else if !engine.state.last_dialogue.is_empty() && engine.state.last_dialogue != "..." {
-                            // Dialogue is finished, clear it
-                            engine.state.last_dialogue = "...".to_string();
-                            visible_chars = 3;
-                        } else {
-                            // Dialogue is empty/cleared, perform action
-                            match menu_state.selected() {
-                                Some(0) => engine.interact("pat_head"),
-                                Some(1) => engine.interact("talk"),
-                                Some(2) => engine.interact("give_treat"),
-                                Some(3) => {
-                                    engine.state.last_dialogue = "Concepto Original: Ray-K\nLógica y TUI: staFF6773 (Rust Port)\nVersión: 1.1.0 (Rust)".to_string();
-                                },
-                                Some(4) => {
-                                    engine.state.last_dialogue = "Buscando actualizaciones...".to_string();
-                                    visible_chars = 0;
-                                    terminal.draw(|f| ui(f, &engine, &mut menu_state, visible_chars, &mut image_state))?;
-                                    match crate::update::check_version() {
-                                        Ok(msg) => engine.state.last_dialogue = msg,
-                                        Err(e) => engine.state.last_dialogue = format!("Error al comprobar versión: {}", e),
-                                    }
+                            Some(MenuEntry::Reply { .. }) => {}
+                            Some(MenuEntry::Saves) => {
+                                slots.refresh(&store);
+                                screen = Screen::Slots;
+                            }
+                            Some(MenuEntry::Settings) => {
+                                settings.message = tr!("settings.saved_in", path = paths.config_file.display());
+                                screen = Screen::Settings;
+                            }
+                            Some(MenuEntry::Credits) => {
+                                engine.state.last_dialogue = tr!("menu.credits_text").to_string();
+                            },
+                            Some(MenuEntry::CheckVersion) => {
+                                // La búsqueda va en otro hilo: sin conexión, la interfaz no se congela
+                                engine.state.last_dialogue = tr!("update.checking").to_string();
+                                if version_check.is_none() {
+                                    version_check = Some(update::check_now_in_background(session.config.updates.clone()));
                                 }
-                                Some(5) => return Ok(()),
-                                _ => {}
                             }
-                            visible_chars = 0;
+                            Some(MenuEntry::Quit) => return Ok(()),
+                            None => {}
                         }
+                        session.typewriter.restart(Instant::now());
                     }
-                    _ => {}
                 }
+                _ => {}
             }
         }
-        else {
-            let dialogue_len = engine.state.last_dialogue.chars().count();
-            if visible_chars < dialogue_len {
-                visible_chars += 1;
-            }
+    }
+}
+
+/// Carga `art/<file>`; si falta (o no se puede decodificar), usa la imagen de reserva del manifiesto.
+fn load_sprite(paths: &Paths, engine: &Engine, file: &str) -> Result<image::DynamicImage, Box<dyn Error>> {
+    let decoded = assets::load(paths, &format!("art/{}", file))
+        .map_err(Box::<dyn Error>::from)
+        .and_then(|asset| image::load_from_memory(&asset.bytes).map_err(Box::<dyn Error>::from));
+    match decoded {
+        Ok(image) => Ok(image),
+        Err(_) => {
+            let asset = assets::load(paths, &format!("art/{}", engine.sprites.fallback))?;
+            Ok(image::load_from_memory(&asset.bytes)?)
         }
     }
 }
 
-fn ui(f: &mut ratatui::Frame, engine: &Engine, menu_state: &mut ListState, visible_chars: usize, image_state: &mut StatefulProtocol) {
+fn ui(f: &mut ratatui::Frame, engine: &Engine, session: &Session, menu: &[MenuEntry], menu_state: &mut ListState, image_state: &mut StatefulProtocol) {
+    let theme = session.theme;
     let chunks = Layout::vertical([
         Constraint::Length(3), // Status bar
         Constraint::Min(10),   // Main area
@@ -119,61 +817,256 @@ fn ui(f: &mut ratatui::Frame, engine: &Engine, menu_state: &mut ListState, visib
     .split(f.area());
 
     // Status Bar
-    let status_text = format!(
-        " [ Día {} ] Afecto: {} (+{}/10) | Confianza: {} (+{}/5) | Hora: {}:{:02} | Fase: {}",
-        engine.state.day,
-        engine.state.affection,
-        engine.state.daily_affection,
-        engine.state.trust,
-        engine.state.daily_trust,
-        engine.state.hour,
-        engine.state.minute,
-        engine.state.time_of_day
+    let status_text = tr!(
+        "status.bar",
+        day = engine.state.day,
+        affection = engine.state.affection,
+        daily_affection = engine.state.daily_affection,
+        affection_cap = engine.actions.caps.affection,
+        trust = engine.state.trust,
+        daily_trust = engine.state.daily_trust,
+        trust_cap = engine.actions.caps.trust,
+        time = format!("{}:{:02}", engine.state.hour, engine.state.minute),
+        phase = i18n::phase(&engine.state.time_of_day),
+        audio = session.audio.label()
     );
+    let mut status_block = Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.status));
+    if let Some(notice) = &session.notification {
+        status_block = status_block.title_bottom(
+            Line::from(format!(" {} · tfeeling-CLI update ", notice)).style(theme.highlight).right_aligned(),
+        );
+    }
     let status_bar = Paragraph::new(status_text)
-        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
-        .style(Style::default().fg(Color::Cyan));
+        .block(status_block)
+        .style(Style::default().fg(theme.status));
     f.render_widget(status_bar, chunks[0]);
 
     // Main Area: Sylvie + Dialogue
+    let portrait = if session.config.display.show_portrait { 40 } else { 0 };
     let main_chunks = Layout::horizontal([
-        Constraint::Percentage(40),
-        Constraint::Percentage(60),
+        Constraint::Percentage(portrait),
+        Constraint::Percentage(100 - portrait),
     ])
     .split(chunks[1]);
 
     // Sylvie Image
-    let sylvie_block = Block::default()
-        .borders(Borders::ALL)
-        .title(" Sylvie ")
-        .border_style(Style::default().fg(Color::Magenta));
-    
-    let inner_area = sylvie_block.inner(main_chunks[0]);
-    f.render_widget(sylvie_block, main_chunks[0]);
-    
-    let image = StatefulImage::new().resize(Resize::Fit(None));
-    f.render_stateful_widget(image, inner_area, image_state);
+    if portrait > 0 {
+        let sylvie_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Sylvie ")
+            .border_style(Style::default().fg(theme.portrait));
+
+        let inner_area = sylvie_block.inner(main_chunks[0]);
+        f.render_widget(sylvie_block, main_chunks[0]);
+
+        let image = StatefulImage::new().resize(Resize::Fit(None));
+        f.render_stateful_widget(image, inner_area, image_state);
+    }
 
     // Dialogue Box
-    let displayed_text: String = engine.state.last_dialogue.chars().take(visible_chars).collect();
+    let typewriter = &session.typewriter;
+    let displayed_text: String = engine.state.last_dialogue.chars().take(typewriter.visible()).collect();
+    let dialogue_title = tr!(
+        "dialogue.title",
+        speed = typewriter.speed.label(),
+        auto = if typewriter.auto_advance.is_some() { tr!("dialogue.auto") } else { "" }
+    );
     let dialogue = Paragraph::new(displayed_text)
-        .block(Block::default().borders(Borders::ALL).title(" Diálogo ").border_style(Style::default().fg(Color::White)))
-        .style(Style::default().fg(Color::White))
+        .block(Block::default().borders(Borders::ALL).title(dialogue_title).border_style(Style::default().fg(theme.text)))
+        .style(Style::default().fg(theme.text))
         .wrap(Wrap { trim: true });
     f.render_widget(dialogue, main_chunks[1]);
 
     // Menu
-    let items = [
-        ListItem::new("1) Acariciar cabeza"),
-        ListItem::new("2) Hablar"),
-        ListItem::new("3) Dar dulce"),
-        ListItem::new("4) Créditos"),
-        ListItem::new("5) Comprobar versión"),
-        ListItem::new("6) Salir"),
-    ];
-    let menu = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title(" Acciones ").border_style(Style::default().fg(Color::Gray)))
-        .highlight_style(Style::default().fg(Color::Black).bg(Color::Magenta).add_modifier(Modifier::BOLD))
+    let items: Vec<ListItem> = menu
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let item = ListItem::new(format!("{}) {}", i + 1, entry.label()));
+            // Las acciones bloqueadas o en enfriamiento se muestran atenuadas
+            match entry {
+                MenuEntry::Action { id, .. }
+                    if engine.actions.get(id).and_then(|a| a.check(&engine.state)).is_err() =>
+                {
+                    item.style(theme.dim)
+                }
+                _ => item,
+            }
+        })
+        .collect();
+    let menu_title = if engine.state.conversation.is_some() { tr!("menu.replies") } else { tr!("menu.actions") };
+    let menu_list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(menu_title).border_style(Style::default().fg(theme.frame)))
+        .highlight_style(theme.highlight)
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(menu_list, chunks[2], menu_state);
+}
+
+fn slots_ui(f: &mut ratatui::Frame, slots: &mut SlotScreen, theme: &Theme) {
+    let chunks = Layout::vertical([
+        Constraint::Length(3), // Title
+        Constraint::Min(5),    // Slot list
+        Constraint::Length(4), // Prompt/help
+    ])
+    .split(f.area());
+
+    let title = Paragraph::new(tr!("saves.title"))
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.status)))
+        .style(Style::default().fg(theme.status));
+    f.render_widget(title, chunks[0]);
+
+    let mut items = vec![ListItem::new(tr!("saves.new"))];
+    items.extend(slots.slots.iter().map(|slot| {
+        let date = slot.modified.map(save::format_timestamp).unwrap_or_else(|| "-".to_string());
+        if let Some(problem) = &slot.problem {
+            return ListItem::new(format!("{:<20} ⚠ {}", slot.name, problem)).style(Style::default().fg(theme.error));
+        }
+        ListItem::new(format!(
+            "{:<20} {}",
+            slot.name,
+            tr!("saves.slot", day = format!("{:<4}", slot.day), affection = format!("{:<4}", slot.affection), trust = format!("{:<4}", slot.trust), date = date)
+        ))
+    }));
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(tr!("saves.slots")).border_style(Style::default().fg(theme.portrait)))
+        .highlight_style(theme.highlight)
         .highlight_symbol(">> ");
-    f.render_stateful_widget(menu, chunks[2], menu_state);
+    f.render_stateful_widget(list, chunks[1], &mut slots.list_state);
+
+    let prompt = match &slots.prompt {
+        Some(Prompt::NewGame(name)) => tr!("saves.prompt_new", name = name),
+        Some(Prompt::Duplicate { from, name }) => tr!("saves.prompt_duplicate", from = from, name = name),
+        Some(Prompt::ConfirmDelete(name)) => tr!("saves.prompt_delete", name = name),
+        None => tr!("saves.help").to_string(),
+    };
+    let help = Paragraph::new(format!("{}\n{}", prompt, slots.message))
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.frame)))
+        .wrap(Wrap { trim: true });
+    f.render_widget(help, chunks[2]);
+}
+
+fn settings_ui(f: &mut ratatui::Frame, settings: &mut SettingsScreen, session: &Session) {
+    let theme = session.theme;
+    let chunks = Layout::vertical([
+        Constraint::Length(3), // Title
+        Constraint::Min(5),    // Options
+        Constraint::Length(4), // Help
+    ])
+    .split(f.area());
+
+    let title = Paragraph::new(tr!("settings.title"))
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.status)))
+        .style(Style::default().fg(theme.status));
+    f.render_widget(title, chunks[0]);
+
+    let items: Vec<ListItem> = SETTINGS
+        .iter()
+        .map(|s| ListItem::new(format!("{:<40} {}", s.label(), s.value(&session.config))))
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(tr!("settings.options")).border_style(Style::default().fg(theme.portrait)))
+        .highlight_style(theme.highlight)
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, chunks[1], &mut settings.list_state);
+
+    let help = Paragraph::new(format!("{}\n{}", tr!("settings.help"), settings.message))
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.frame)))
+        .wrap(Wrap { trim: true });
+    f.render_widget(help, chunks[2]);
+}
+
+fn changelog_ui(f: &mut ratatui::Frame, changelog: &mut ChangelogScreen, theme: &Theme) {
+    let chunks = Layout::vertical([
+        Constraint::Length(3), // Title
+        Constraint::Min(5),    // Notes
+        Constraint::Length(4), // Progress/help
+    ])
+    .split(f.area());
+
+    let latest = changelog.releases.first().map(|r| r.version.as_str()).unwrap_or("-");
+    let title = Paragraph::new(tr!("changelog.title", version = latest))
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.status)))
+        .style(Style::default().fg(theme.status));
+    f.render_widget(title, chunks[0]);
+
+    let notes = Paragraph::new(changelog.lines.clone())
+        .block(Block::default().borders(Borders::ALL).title(tr!("changelog.notes")).border_style(Style::default().fg(theme.portrait)))
+        .style(Style::default().fg(theme.text))
+        .wrap(Wrap { trim: false })
+        .scroll((changelog.scroll, 0));
+    f.render_widget(notes, chunks[1]);
+
+    let block = Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.frame));
+    if changelog.install.is_some() {
+        let (done, total) = changelog.progress;
+        let (ratio, label) = match total {
+            // Descargado: falta verificar, extraer y sustituir el binario
+            Some(total) if total > 0 && done >= total => (1.0, tr!("update.verifying", version = latest)),
+            Some(total) if total > 0 => (done as f64 / total as f64, tr!("update.downloading_percent", version = latest, percent = done * 100 / total)),
+            _ => (0.0, tr!("update.downloading_kib", version = latest, kib = done / 1024)),
+        };
+        let gauge = Gauge::default().block(block).gauge_style(theme.highlight).ratio(ratio.clamp(0.0, 1.0)).label(label);
+        f.render_widget(gauge, chunks[2]);
+    } else {
+        let help = if changelog.message.is_empty() {
+            tr!("changelog.help_install")
+        } else {
+            tr!("changelog.help")
+        };
+        let help = Paragraph::new(format!("{}\n{}", help, changelog.message)).block(block).wrap(Wrap { trim: true });
+        f.render_widget(help, chunks[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typewriter::TextSpeed;
+
+    #[test]
+    fn test_only_changed_settings_are_saved() {
+        // `--text-speed fast` sólo vale para esta sesión
+        let saved = Config::default();
+        let mut config = saved.clone();
+        config.text.speed = TextSpeed::Fast;
+
+        Setting::Volume.change(&mut config, false);
+        let result = settings_to_save(&saved, &config, &[Setting::Volume]);
+        assert_eq!(result.text.speed, TextSpeed::Normal);
+        assert_eq!(result.audio.volume, 0.6);
+
+        // Si el jugador cambia la velocidad a propósito, sí se guarda
+        let result = settings_to_save(&saved, &config, &[Setting::TextSpeed]);
+        assert_eq!(result.text.speed, TextSpeed::Fast);
+    }
+
+    #[test]
+    fn test_sprite_swap_from_data_dir() {
+        // El juego sólo trae el retrato base: las expresiones llegan desde el directorio de datos
+        let dir = std::env::temp_dir().join("tfeeling_test_sprites");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("art")).unwrap();
+        std::fs::write(dir.join("art/sprites.json"), r#"{
+            "fallback": "Sylvie-base.png",
+            "expressions": [
+                { "name": "scared", "file": "Sylvie-scared.png", "when": "affection < 20" },
+                { "name": "smiling", "file": "Sylvie-smiling.png" }
+            ]
+        }"#).unwrap();
+        image::RgbaImage::new(2, 3).save(dir.join("art/Sylvie-smiling.png")).unwrap();
+        let paths = Paths { data_dir: dir.clone(), save_dir: dir.join("saves"), config_file: dir.join("config.toml"), cache_dir: dir.join("cache") };
+        let mut engine = Engine::new(&paths).unwrap();
+
+        engine.state.affection = 60;
+        let (name, file) = engine.sprites.select(&engine.state);
+        assert_eq!(name, "smiling");
+        let smiling = load_sprite(&paths, &engine, file).unwrap();
+        assert_eq!((smiling.width(), smiling.height()), (2, 3));
+
+        // `Sylvie-scared.png` no existe: se muestra el retrato base
+        engine.state.affection = 0;
+        let (name, file) = engine.sprites.select(&engine.state);
+        assert_eq!(name, "scared");
+        assert_ne!(load_sprite(&paths, &engine, file).unwrap().width(), 2);
+    }
 }
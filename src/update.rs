@@ -1,42 +1,644 @@
-use self_update::backends::github::Update;
-use std::error::Error;
-
-pub fn update() -> Result<(), Box<dyn Error>> {
-    let status = Update::configure()
-        .repo_owner("staFF6773")
-        .repo_name("tfeeling-CLI")
-        .bin_name("tfeeling-CLI")
-        .show_download_progress(true)
-        .current_version(env!("CARGO_PKG_VERSION"))
-        .build()?
-        .update()?;
-    
-    if status.updated() {
-        println!("¡Actualizado a la versión {}!", status.version());
-    } else {
-        println!("Ya estás en la versión más reciente: {}", status.version());
+use crate::config::UpdateConfig;
+use crate::i18n::tr;
+use crate::release::{self, Progress, Release, ReleaseAsset, ReleaseSource, UpdateError, BIN_NAME};
+use crate::verify;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const CURRENT: &str = env!("CARGO_PKG_VERSION");
+
+/// Si las preferencias de `config` permiten ofrecer `version`. Una versión fijada
+/// tiene prioridad sobre el canal y las omitidas.
+fn is_offered(version: &semver::Version, config: &UpdateConfig) -> bool {
+    let same = |v: &String| release::parse_version(v).as_ref() == Some(version);
+    match &config.pin {
+        Some(pin) => same(pin),
+        None => config.channel.allows(version) && !config.skip.iter().any(same),
+    }
+}
+
+/// La versión más alta que se puede ofrecer y tiene binario para este sistema.
+fn latest<'a>(releases: &'a [Release], config: &UpdateConfig) -> Option<&'a Release> {
+    let target = self_update::get_target();
+    releases
+        .iter()
+        .filter(|r| r.asset_for(target).is_some())
+        .filter_map(|r| r.semver().map(|v| (v, r)))
+        .filter(|(v, _)| is_offered(v, config))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, r)| r)
+}
+
+fn is_newer(release: &Release) -> bool {
+    match (release.semver(), release::parse_version(CURRENT)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+fn is_current(release: &Release) -> bool {
+    release.semver().is_some() && release.semver() == release::parse_version(CURRENT)
+}
+
+/// Resultado de `update` y `rollback`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum UpdateOutcome {
+    /// `verified` es `false` si la versión no publicaba `SHA256SUMS` y se instaló sin comprobar.
+    Installed { from: String, to: String, verified: bool },
+    UpToDate { current: String },
+    RolledBack,
+}
+
+impl fmt::Display for UpdateOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UpdateOutcome::Installed { to, verified, .. } => {
+                write!(f, "{}", tr!("update.installed", version = to))?;
+                if !verified {
+                    write!(f, "\n{}", unverified_warning(to))?;
+                }
+                Ok(())
+            }
+            UpdateOutcome::UpToDate { current } => write!(f, "{}", tr!("update.up_to_date", version = current)),
+            UpdateOutcome::RolledBack => write!(f, "{}", tr!("update.rolled_back")),
+        }
     }
-    
-    Ok(())
 }
 
-pub fn check_version() -> Result<String, Box<dyn Error>> {
-    let releases = self_update::backends::github::ReleaseList::configure()
-        .repo_owner("staFF6773")
-        .repo_name("tfeeling-CLI")
-        .build()?
-        .fetch()?;
+/// Versión que instalaría `update`: `to` si se indica, la fijada o la más reciente
+/// que se ofrece. `None` si no hay que instalar nada.
+fn select<'a>(releases: &'a [Release], config: &UpdateConfig, to: Option<&str>) -> Result<Option<&'a Release>, UpdateError> {
+    let release = match to {
+        Some(to) => {
+            let wanted = release::parse_version(to);
+            let found = releases.iter().find(|r| wanted.is_some() && r.semver() == wanted);
+            Some(found.ok_or_else(|| UpdateError::UnknownVersion(to.to_string()))?)
+        }
+        None => latest(releases, config),
+    };
+    // Una versión pedida o fijada se instala aunque sea anterior
+    Ok(release.filter(|r| !is_current(r) && (is_newer(r) || to.is_some() || config.pin.is_some())))
+}
 
-    if releases.is_empty() {
-        return Ok("No se encontraron releases en GitHub.".to_string());
+/// Instala la versión más reciente, o `to` si se indica (aunque sea anterior).
+pub fn update(config: &UpdateConfig, to: Option<&str>, progress: Progress) -> Result<UpdateOutcome, UpdateError> {
+    let source = config.source.open();
+    let releases = source.releases()?;
+    match select(&releases, config, to)? {
+        Some(release) => {
+            let verified = install(&*source, config, release, progress)?;
+            Ok(UpdateOutcome::Installed { from: CURRENT.to_string(), to: release.version.clone(), verified })
+        }
+        None => Ok(UpdateOutcome::UpToDate { current: CURRENT.to_string() }),
     }
+}
+
+/// Una entrada de `update --list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedRelease {
+    pub version: String,
+    pub date: String,
+    pub current: bool,
+    pub prerelease: bool,
+    pub pinned: bool,
+    pub skipped: bool,
+    /// Si hay binario para este sistema.
+    pub available: bool,
+}
 
-    let latest = &releases[0];
-    let current = env!("CARGO_PKG_VERSION");
+/// Versiones publicadas, de la más reciente a la más antigua.
+pub fn list(config: &UpdateConfig) -> Result<Vec<ListedRelease>, UpdateError> {
+    Ok(listed(&config.source.open().releases()?, config))
+}
+
+fn listed(releases: &[Release], config: &UpdateConfig) -> Vec<ListedRelease> {
+    let mut versions: Vec<_> = releases.iter().filter_map(|r| r.semver().map(|v| (v, r))).collect();
+    versions.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    let target = self_update::get_target();
+    let same = |v: &String, version: &semver::Version| release::parse_version(v).as_ref() == Some(version);
+    versions
+        .into_iter()
+        .map(|(version, release)| ListedRelease {
+            version: version.to_string(),
+            date: release.date.clone(),
+            current: is_current(release),
+            prerelease: !version.pre.is_empty(),
+            pinned: config.pin.iter().any(|v| same(v, &version)),
+            skipped: config.skip.iter().any(|v| same(v, &version)),
+            available: release.asset_for(target).is_some(),
+        })
+        .collect()
+}
+
+/// Copia del binario que sustituyó la última actualización, junto al ejecutable.
+fn previous_path(exe: &Path) -> PathBuf {
+    let mut name = exe.file_name().unwrap_or_default().to_os_string();
+    name.push(".previous");
+    exe.with_file_name(name)
+}
+
+/// Vuelve al binario guardado por la última actualización.
+pub fn rollback() -> Result<UpdateOutcome, UpdateError> {
+    let exe = std::env::current_exe()?;
+    let previous = previous_path(&exe);
+    if !previous.is_file() {
+        return Err(UpdateError::NoPrevious(previous));
+    }
+    self_update::self_replace::self_replace(&previous)?;
+    std::fs::remove_file(&previous)?;
+    Ok(UpdateOutcome::RolledBack)
+}
+
+fn download_text(source: &dyn ReleaseSource, asset: &ReleaseAsset) -> Result<String, UpdateError> {
+    let mut bytes = Vec::new();
+    source.download(asset, &mut bytes, &mut |_, _| {})?;
+    String::from_utf8(bytes).map_err(|_| UpdateError::Verification(tr!("verify.not_text", file = asset.name)))
+}
 
-    if self_update::version::bump_is_greater(current, &latest.version)? {
-        Ok(format!("Nueva versión disponible: v{} (Actual: v{})", latest.version, current))
+/// Aviso para una versión instalada sin `SHA256SUMS`.
+pub fn unverified_warning(version: &str) -> String {
+    tr!("update.unverified", version = version, file = verify::SUMS_FILE)
+}
+
+/// Comprueba `archive` contra el `SHA256SUMS` de `release` y, con clave pública, su firma.
+/// Devuelve `false` si no había sumas y se acepta sin comprobar (`require_checksums = false`).
+fn verify_archive(source: &dyn ReleaseSource, config: &UpdateConfig, release: &Release, name: &str, archive: &Path) -> Result<bool, UpdateError> {
+    let Some(sums_asset) = release.asset(verify::SUMS_FILE) else {
+        if config.require_checksums || config.public_key.is_some() {
+            return Err(UpdateError::Verification(tr!(
+                "verify.no_sums",
+                version = release.version,
+                file = verify::SUMS_FILE
+            )));
+        }
+        return Ok(false);
+    };
+    let sums = download_text(source, sums_asset)?;
+
+    if let Some(key) = &config.public_key {
+        let sig_asset = release.asset(verify::SIGNATURE_FILE).ok_or_else(|| {
+            UpdateError::Verification(tr!("verify.missing_file", version = release.version, file = verify::SIGNATURE_FILE))
+        })?;
+        let signature = download_text(source, sig_asset)?;
+        verify::check_signature(key, sums.as_bytes(), &signature).map_err(UpdateError::Verification)?;
+    }
+    verify::check_sum(&sums, name, archive).map_err(UpdateError::Verification)?;
+    Ok(true)
+}
+
+/// Descarga el tarball de `release`, lo verifica, extrae el binario y sustituye al
+/// ejecutable actual, guardando una copia de éste para `update --rollback`. Devuelve
+/// si se comprobó la suma (ver `verify_archive`).
+fn install(source: &dyn ReleaseSource, config: &UpdateConfig, release: &Release, progress: Progress) -> Result<bool, UpdateError> {
+    let target = self_update::get_target();
+    let asset = release.asset_for(target).ok_or_else(|| UpdateError::NoAsset {
+        version: release.version.clone(),
+        target: target.to_string(),
+    })?;
+
+    let tmp = self_update::TempDir::new()?;
+    let archive = tmp.path().join(&asset.name);
+    let mut file = std::fs::File::create(&archive)?;
+    source.download(asset, &mut file, progress)?;
+    file.sync_all()?;
+    let verified = verify_archive(source, config, release, &asset.name, &archive)?;
+
+    self_update::Extract::from_source(&archive)
+        .archive(self_update::ArchiveKind::Tar(Some(self_update::Compression::Gz)))
+        .extract_file(tmp.path(), BIN_NAME)
+        .map_err(|e| UpdateError::Archive(e.to_string()))?;
+    let new_bin = tmp.path().join(BIN_NAME);
+
+    // Un binario que ni siquiera responde a --version no llega a instalarse
+    let starts = std::process::Command::new(&new_bin)
+        .arg("--version")
+        .output()
+        .map(|out| out.status.success())
+        .unwrap_or(false);
+    if !starts {
+        return Err(UpdateError::Archive(tr!("update.broken_binary", version = release.version)));
+    }
+
+    let exe = std::env::current_exe()?;
+    std::fs::copy(&exe, previous_path(&exe))?;
+    self_update::self_replace::self_replace(&new_bin)?;
+    Ok(verified)
+}
+
+/// Busca una versión nueva en `source`. Los fallos (p. ej. sin conexión) se
+/// devuelven como `VersionCheck::Error`.
+pub fn check_version(source: &dyn ReleaseSource, config: &UpdateConfig) -> VersionCheck {
+    match source.releases() {
+        Ok(releases) => check(&releases, &source.describe(), config),
+        Err(e) => VersionCheck::Error { message: e.to_string() },
+    }
+}
+
+/// Resultado de buscar una versión nueva. Es lo que escribe `update --check --json`,
+/// así que los nombres de las variantes y campos forman parte de la salida estable
+/// (salvo `Error`, que se escribe como `{"error": "..."}`, igual que en las demás órdenes).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum VersionCheck {
+    UpToDate { current: String },
+    UpdateAvailable { current: String, latest: String, notes: String },
+    NoReleases { source: String },
+    Error { message: String },
+}
+
+impl fmt::Display for VersionCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VersionCheck::UpToDate { current } => write!(f, "{}", tr!("update.check_up_to_date", version = current)),
+            VersionCheck::UpdateAvailable { current, latest, .. } => {
+                write!(f, "{}", tr!("update.check_available", latest = latest, current = current))
+            }
+            VersionCheck::NoReleases { source } => write!(f, "{}", tr!("update.no_releases", source = source)),
+            VersionCheck::Error { message } => write!(f, "{}: {}", tr!("update.check_failed"), message),
+        }
+    }
+}
+
+/// Compara la versión actual con unas versiones ya descargadas de `origin`.
+pub fn check(releases: &[Release], origin: &str, config: &UpdateConfig) -> VersionCheck {
+    let Some(latest) = latest(releases, config) else {
+        return VersionCheck::NoReleases { source: origin.to_string() };
+    };
+
+    if is_newer(latest) {
+        VersionCheck::UpdateAvailable {
+            current: CURRENT.to_string(),
+            latest: latest.version.clone(),
+            notes: latest.notes.clone(),
+        }
     } else {
-        Ok(format!("Estás actualizado. Versión: v{}", current))
+        VersionCheck::UpToDate { current: CURRENT.to_string() }
+    }
+}
+
+/// Versiones posteriores a la actual hasta la más reciente que se ofrece, de la más
+/// nueva a la más antigua, sin las que excluyen el canal, `skip` o `pin`. La primera
+/// es la que se instalaría; vacío si no hay ninguna.
+pub fn pending(releases: &[Release], config: &UpdateConfig) -> Vec<Release> {
+    let Some(top) = latest(releases, config).filter(|r| is_newer(r)).and_then(|r| r.semver()) else {
+        return Vec::new();
+    };
+    let current = release::parse_version(CURRENT);
+    let mut pending: Vec<_> = releases
+        .iter()
+        .filter_map(|r| r.semver().map(|v| (v, r)))
+        .filter(|(v, _)| Some(v) > current.as_ref() && *v <= top && (is_offered(v, config) || *v == top))
+        .collect();
+    pending.sort_by(|(a, _), (b, _)| b.cmp(a));
+    pending.into_iter().map(|(_, r)| r.clone()).collect()
+}
+
+pub enum InstallEvent {
+    Progress(u64, Option<u64>),
+    /// `Ok(false)`: instalada sin `SHA256SUMS` que comprobar.
+    Done(Result<bool, UpdateError>),
+}
+
+/// Instala `release` en otro hilo; el receptor informa del progreso de la descarga
+/// y, al final, del resultado.
+pub fn install_in_background(config: UpdateConfig, release: Release) -> Receiver<InstallEvent> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let progress = tx.clone();
+        let result = install(&*config.source.open(), &config, &release, &mut |done, total| {
+            let _ = progress.send(InstallEvent::Progress(done, total));
+        });
+        let _ = tx.send(InstallEvent::Done(result));
+    });
+    rx
+}
+
+/// Última comprobación, para no consultar GitHub en cada arranque.
+#[derive(Serialize, Deserialize)]
+struct CheckCache {
+    /// Segundos desde la época Unix.
+    checked_at: u64,
+    /// Versión que hizo la comprobación; tras actualizar, la caché ya no vale.
+    #[serde(default)]
+    version: String,
+    /// Versión nueva encontrada (`VersionCheck::UpdateAvailable`), si la hubo.
+    update: Option<VersionCheck>,
+    /// Preferencias con las que se buscó (`cache_key`); si cambian, la caché ya no vale.
+    #[serde(default)]
+    preferences: String,
+}
+
+/// Resume lo que decide qué versión se ofrece: fuente, canal, `pin` y `skip`.
+fn cache_key(config: &UpdateConfig) -> String {
+    serde_json::to_string(&(&config.source, config.channel, &config.pin, &config.skip)).unwrap_or_default()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn read_cache(path: &Path) -> Option<CheckCache> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+fn write_cache(path: &Path, cache: &CheckCache) {
+    // La caché es prescindible: si no se puede escribir, se comprobará otra vez
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(content) = serde_json::to_string(cache) {
+        let _ = std::fs::write(path, content);
+    }
+}
+
+/// Busca una versión nueva en otro hilo, sin caché (la opción **Comprobar versión**).
+/// El receptor entrega el resultado y, si hay versión nueva, las versiones pendientes
+/// (ver `pending`) para mostrar sus notas.
+pub fn check_now_in_background(config: UpdateConfig) -> Receiver<(VersionCheck, Vec<Release>)> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let source = config.source.open();
+        let result = match source.releases() {
+            Ok(releases) => (check(&releases, &source.describe(), &config), pending(&releases, &config)),
+            Err(e) => (VersionCheck::Error { message: e.to_string() }, Vec::new()),
+        };
+        let _ = tx.send(result);
+    });
+    rx
+}
+
+/// Busca actualizaciones en segundo plano. El receptor entrega el resultado sólo si
+/// hay una versión nueva; los errores (p. ej. sin conexión) se ignoran en silencio.
+///
+/// Si la última comprobación guardada en `cache_file` tiene menos de `interval`,
+/// no se consulta la red y se repite el aviso guardado, si lo había.
+pub fn check_in_background(config: UpdateConfig, cache_file: PathBuf, interval: Duration) -> Receiver<VersionCheck> {
+    let (tx, rx) = mpsc::channel();
+    if let Some(cache) = read_cache(&cache_file)
+        && cache.version == CURRENT
+        && cache.preferences == cache_key(&config)
+        && now_secs().saturating_sub(cache.checked_at) < interval.as_secs()
+    {
+        if let Some(update) = cache.update {
+            let _ = tx.send(update);
+        }
+        return rx;
+    }
+
+    std::thread::spawn(move || {
+        let update = match check_version(&*config.source.open(), &config) {
+            VersionCheck::Error { .. } => return,
+            check @ VersionCheck::UpdateAvailable { .. } => Some(check),
+            _ => None,
+        };
+        let cache = CheckCache {
+            checked_at: now_secs(),
+            version: CURRENT.to_string(),
+            update: update.clone(),
+            preferences: cache_key(&config),
+        };
+        write_cache(&cache_file, &cache);
+        if let Some(update) = update {
+            let _ = tx.send(update);
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::release::SourceConfig;
+
+    #[test]
+    fn test_recent_cache_skips_network() {
+        let path = std::env::temp_dir().join("tfeeling_test_update").join("update-check.json");
+        let notice = VersionCheck::UpdateAvailable {
+            current: CURRENT.to_string(),
+            latest: "9.9.9".to_string(),
+            notes: String::new(),
+        };
+        let cache = CheckCache {
+            checked_at: now_secs(),
+            version: CURRENT.to_string(),
+            update: Some(notice.clone()),
+            preferences: cache_key(&UpdateConfig::default()),
+        };
+        write_cache(&path, &cache);
+
+        let rx = check_in_background(UpdateConfig::default(), path.clone(), Duration::from_secs(3600));
+        assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), notice);
+
+        // Omitir una versión invalida el aviso guardado: se vuelve a buscar (aquí, sin éxito)
+        let missing = SourceConfig::Local { path: std::env::temp_dir().join("tfeeling_test_no_existe") };
+        write_cache(&path, &CheckCache { preferences: cache_key(&UpdateConfig { source: missing.clone(), ..Default::default() }), ..cache });
+        let skip = UpdateConfig { source: missing, skip: vec!["9.9.9".to_string()], ..Default::default() };
+        let rx = check_in_background(skip, path, Duration::from_secs(3600));
+        assert!(rx.recv_timeout(Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_check_is_structured() {
+        let ours = format!("tfeeling-CLI-{}.tar.gz", self_update::get_target());
+        let release = |version: &str| Release {
+            version: version.to_string(),
+            date: String::new(),
+            notes: "Arreglos".to_string(),
+            assets: vec![crate::release::ReleaseAsset { name: ours.clone(), url: String::new() }],
+        };
+        let config = UpdateConfig::default();
+
+        let result = check(&[release(CURRENT), release("9.0.0")], "espejo", &config);
+        assert_eq!(
+            result,
+            VersionCheck::UpdateAvailable { current: CURRENT.to_string(), latest: "9.0.0".to_string(), notes: "Arreglos".to_string() }
+        );
+        assert_eq!(serde_json::to_value(&result).unwrap()["status"], "update_available");
+        assert_eq!(check(&[release(CURRENT)], "espejo", &config), VersionCheck::UpToDate { current: CURRENT.to_string() });
+        assert_eq!(check(&[], "espejo", &config), VersionCheck::NoReleases { source: "espejo".to_string() });
+
+        let missing = SourceConfig::Local { path: std::env::temp_dir().join("tfeeling_test_no_existe") };
+        assert!(matches!(check_version(&*missing.open(), &config), VersionCheck::Error { .. }));
+
+        // "Comprobar versión" entrega también los errores, sin bloquear a quien llama
+        let rx = check_now_in_background(UpdateConfig { source: missing, ..Default::default() });
+        let (check, pending) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(check, VersionCheck::Error { .. }) && pending.is_empty());
+    }
+
+    #[test]
+    fn test_latest_follows_channel_and_pins() {
+        let target = self_update::get_target();
+        let release = |version: &str, asset: &str| Release {
+            version: version.to_string(),
+            date: String::new(),
+            notes: String::new(),
+            assets: vec![crate::release::ReleaseAsset { name: asset.to_string(), url: String::new() }],
+        };
+        let ours = format!("tfeeling-CLI-{}.tar.gz", target);
+        let releases = vec![
+            release("1.2.0", &ours),
+            release("1.10.0", &ours),
+            release("9.0.0", "tfeeling-CLI-otro-sistema.tar.gz"),
+            release("no-es-versión", &ours),
+            release("1.11.0-beta.1", &ours),
+        ];
+        let stable = UpdateConfig::default();
+        assert_eq!(latest(&releases, &stable).unwrap().version, "1.10.0");
+
+        let beta = UpdateConfig { channel: crate::release::Channel::Beta, ..Default::default() };
+        assert_eq!(latest(&releases, &beta).unwrap().version, "1.11.0-beta.1");
+
+        let skip = UpdateConfig { skip: vec!["v1.10.0".to_string()], ..Default::default() };
+        assert_eq!(latest(&releases, &skip).unwrap().version, "1.2.0");
+
+        // La versión fijada se ofrece aunque sea beta u omitida
+        let pin = UpdateConfig { pin: Some("1.11.0-beta.1".to_string()), skip: vec!["1.11.0-beta.1".to_string()], ..Default::default() };
+        assert_eq!(latest(&releases, &pin).unwrap().version, "1.11.0-beta.1");
+    }
+
+    #[test]
+    fn test_pending_lists_versions_since_current() {
+        let ours = format!("tfeeling-CLI-{}.tar.gz", self_update::get_target());
+        let release = |version: &str| Release {
+            version: version.to_string(),
+            date: String::new(),
+            notes: format!("Notas de {}", version),
+            assets: vec![crate::release::ReleaseAsset { name: ours.clone(), url: String::new() }],
+        };
+        let releases = vec![release("0.0.1"), release(CURRENT), release("9.0.1"), release("9.0.0-beta.1"), release("9.0.0")];
+
+        let versions: Vec<_> = pending(&releases, &UpdateConfig::default()).into_iter().map(|r| r.version).collect();
+        assert_eq!(versions, vec!["9.0.1", "9.0.0"]);
+
+        let beta_config = UpdateConfig { channel: crate::release::Channel::Beta, ..Default::default() };
+        assert_eq!(pending(&releases, &beta_config).len(), 3);
+        assert!(pending(&releases[..2], &UpdateConfig::default()).is_empty());
+
+        // Las omitidas no aparecen, ni como la que se instalaría ni entre las notas
+        let skip = UpdateConfig { skip: vec!["9.0.1".to_string()], ..Default::default() };
+        let versions: Vec<_> = pending(&releases, &skip).into_iter().map(|r| r.version).collect();
+        assert_eq!(versions, vec!["9.0.0"]);
+        let skip = UpdateConfig { skip: vec!["9.0.0".to_string()], ..Default::default() };
+        let versions: Vec<_> = pending(&releases, &skip).into_iter().map(|r| r.version).collect();
+        assert_eq!(versions, vec!["9.0.1"]);
+
+        // Con una versión fijada, sólo ésa
+        let pin = UpdateConfig { pin: Some("9.0.0".to_string()), ..Default::default() };
+        let versions: Vec<_> = pending(&releases, &pin).into_iter().map(|r| r.version).collect();
+        assert_eq!(versions, vec!["9.0.0"]);
+    }
+
+    #[test]
+    fn test_select_and_list() {
+        let ours = format!("tfeeling-CLI-{}.tar.gz", self_update::get_target());
+        let release = |version: &str| Release {
+            version: version.to_string(),
+            date: String::new(),
+            notes: String::new(),
+            assets: vec![crate::release::ReleaseAsset { name: ours.clone(), url: String::new() }],
+        };
+        let releases = vec![release("0.0.1"), release(CURRENT), release("9.0.0"), release("9.1.0-beta.1")];
+        let version = |config: &UpdateConfig, to: Option<&str>| select(&releases, config, to).unwrap().map(|r| r.version.as_str());
+
+        let config = UpdateConfig::default();
+        assert_eq!(version(&config, None), Some("9.0.0"));
+        // `--to` instala aunque sea anterior, beta u omitida, pero no la actual
+        assert_eq!(version(&config, Some("v0.0.1")), Some("0.0.1"));
+        assert_eq!(version(&config, Some("9.1.0-beta.1")), Some("9.1.0-beta.1"));
+        assert_eq!(version(&UpdateConfig { skip: vec!["9.0.0".to_string()], ..Default::default() }, Some("9.0.0")), Some("9.0.0"));
+        assert_eq!(version(&config, Some(CURRENT)), None);
+        assert!(matches!(select(&releases, &config, Some("7.0.0")), Err(UpdateError::UnknownVersion(_))));
+
+        // Omitida la única más nueva, no hay nada que instalar
+        assert_eq!(version(&UpdateConfig { skip: vec!["9.0.0".to_string()], ..Default::default() }, None), None);
+        // La versión fijada se instala aunque sea anterior
+        assert_eq!(version(&UpdateConfig { pin: Some("0.0.1".to_string()), ..Default::default() }, None), Some("0.0.1"));
+
+        let pin = UpdateConfig { pin: Some("9.0.0".to_string()), skip: vec!["0.0.1".to_string()], ..Default::default() };
+        let list = listed(&releases, &pin);
+        let flags: Vec<_> = list.iter().map(|r| (r.version.as_str(), r.current, r.prerelease, r.pinned, r.skipped)).collect();
+        assert_eq!(flags, vec![
+            ("9.1.0-beta.1", false, true, false, false),
+            ("9.0.0", false, false, true, false),
+            (CURRENT, true, false, false, false),
+            ("0.0.1", false, false, false, true),
+        ]);
+    }
+
+    #[test]
+    fn test_verify_archive() {
+        let dir = std::env::temp_dir().join("tfeeling_test_update_verify");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let name = format!("tfeeling-CLI-2.0.0-{}.tar.gz", self_update::get_target());
+        let archive = dir.join(&name);
+        std::fs::write(&archive, b"hola").unwrap();
+
+        let source = SourceConfig::Local { path: dir.clone() }.open();
+        let config = UpdateConfig { require_checksums: true, ..Default::default() };
+        let release = |source: &dyn ReleaseSource| source.releases().unwrap().remove(0);
+
+        // Sin SHA256SUMS sólo se acepta si no se exige (lo normal, de momento)
+        assert!(verify_archive(&*source, &config, &release(&*source), &name, &archive).is_err());
+        assert!(!verify_archive(&*source, &UpdateConfig::default(), &release(&*source), &name, &archive).unwrap());
+        let outcome = UpdateOutcome::Installed { from: CURRENT.to_string(), to: "2.0.0".to_string(), verified: false };
+        assert!(outcome.to_string().ends_with(&unverified_warning("2.0.0")));
+
+        let sum = verify::sha256_file(&archive).unwrap();
+        std::fs::write(dir.join(verify::SUMS_FILE), format!("{}  {}\n", sum, name)).unwrap();
+        assert!(verify_archive(&*source, &config, &release(&*source), &name, &archive).unwrap());
+
+        // Con clave pública hace falta la firma
+        let signed = UpdateConfig { public_key: Some("00".repeat(32)), ..Default::default() };
+        assert!(verify_archive(&*source, &signed, &release(&*source), &name, &archive).is_err());
+
+        // Si hay sumas, se comprueban aunque no se exijan
+        std::fs::write(&archive, b"adios").unwrap();
+        assert!(verify_archive(&*source, &config, &release(&*source), &name, &archive).is_err());
+        assert!(verify_archive(&*source, &UpdateConfig::default(), &release(&*source), &name, &archive).is_err());
+    }
+
+    #[test]
+    fn test_verify_archive_signature() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let dir = std::env::temp_dir().join("tfeeling_test_update_signature");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let name = format!("tfeeling-CLI-2.0.0-{}.tar.gz", self_update::get_target());
+        let archive = dir.join(&name);
+        std::fs::write(&archive, b"hola").unwrap();
+        let sums = format!("{}  {}\n", verify::sha256_file(&archive).unwrap(), name);
+        std::fs::write(dir.join(verify::SUMS_FILE), &sums).unwrap();
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let config = UpdateConfig { public_key: Some(hex::encode(key.verifying_key().to_bytes())), ..Default::default() };
+        let source = SourceConfig::Local { path: dir.clone() }.open();
+        let verify = |config: &UpdateConfig| verify_archive(&*source, config, &source.releases().unwrap().remove(0), &name, &archive);
+
+        // Firma correcta
+        std::fs::write(dir.join(verify::SIGNATURE_FILE), hex::encode(key.sign(sums.as_bytes()).to_bytes())).unwrap();
+        assert!(verify(&config).is_ok());
+
+        // Firmado con otra clave
+        let other = SigningKey::from_bytes(&[8u8; 32]);
+        std::fs::write(dir.join(verify::SIGNATURE_FILE), hex::encode(other.sign(sums.as_bytes()).to_bytes())).unwrap();
+        assert!(matches!(verify(&config), Err(UpdateError::Verification(_))));
+
+        // SHA256SUMS cambiado después de firmarlo
+        std::fs::write(dir.join(verify::SIGNATURE_FILE), hex::encode(key.sign(sums.as_bytes()).to_bytes())).unwrap();
+        std::fs::write(dir.join(verify::SUMS_FILE), format!("{}  {}\n", "0".repeat(64), name)).unwrap();
+        assert!(matches!(verify(&config), Err(UpdateError::Verification(_))));
+
+        // Firma ilegible
+        std::fs::write(dir.join(verify::SUMS_FILE), &sums).unwrap();
+        std::fs::write(dir.join(verify::SIGNATURE_FILE), "no es hex").unwrap();
+        assert!(matches!(verify(&config), Err(UpdateError::Verification(_))));
     }
 }
@@ -0,0 +1,92 @@
+//! Comprobación de las descargas del actualizador.
+//!
+//! Cada versión publica `SHA256SUMS` (formato de `sha256sum`) y, opcionalmente,
+//! `SHA256SUMS.sig`: la firma Ed25519 de ese archivo en hexadecimal. La firma sólo
+//! se exige si `updates.public_key` está configurada.
+
+use crate::i18n::tr;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+pub const SUMS_FILE: &str = "SHA256SUMS";
+pub const SIGNATURE_FILE: &str = "SHA256SUMS.sig";
+
+/// Busca en `sums` la suma de `name`. Acepta el modo binario de `sha256sum` (`*nombre`).
+pub fn expected_sum<'a>(sums: &'a str, name: &str) -> Option<&'a str> {
+    sums.lines().find_map(|line| {
+        let (sum, file) = line.split_once(char::is_whitespace)?;
+        let file = file.trim_start().trim_start_matches('*');
+        (file == name).then_some(sum)
+    })
+}
+
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            return Ok(hex::encode(hasher.finalize()));
+        }
+        hasher.update(&buf[..n]);
+    }
+}
+
+/// Comprueba que `file` coincide con su entrada en `sums`.
+pub fn check_sum(sums: &str, name: &str, file: &Path) -> Result<(), String> {
+    let expected = expected_sum(sums, name).ok_or_else(|| tr!("verify.not_listed", file = name, sums = SUMS_FILE))?;
+    let actual = sha256_file(file).map_err(|e| e.to_string())?;
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(tr!("verify.mismatch", file = name, expected = expected, actual = actual))
+    }
+}
+
+/// Comprueba la firma de `message` con la clave pública `public_key`, ambas en hexadecimal.
+pub fn check_signature(public_key: &str, message: &[u8], signature: &str) -> Result<(), String> {
+    let key: [u8; 32] = hex::decode(public_key.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(tr!("verify.bad_key"))?;
+    let key = VerifyingKey::from_bytes(&key).map_err(|e| format!("updates.public_key: {}", e))?;
+    let signature: [u8; 64] = hex::decode(signature.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| tr!("verify.bad_signature_file", file = SIGNATURE_FILE))?;
+    key.verify(message, &Signature::from_bytes(&signature))
+        .map_err(|_| tr!("verify.bad_signature", file = SUMS_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn test_checksums() {
+        let path = std::env::temp_dir().join("tfeeling_test_verify.tar.gz");
+        std::fs::write(&path, b"hola").unwrap();
+        let sum = "b221d9dbb083a7f33428d7c2a3c3198ae925614d70210e28716ccaa7cd4ddb79";
+        let sums = format!("0000  otro.tar.gz\n{} *paquete.tar.gz\n", sum);
+
+        assert_eq!(sha256_file(&path).unwrap(), sum);
+        assert!(check_sum(&sums, "paquete.tar.gz", &path).is_ok());
+        assert!(check_sum(&sums, "otro.tar.gz", &path).unwrap_err().contains("no coincide"));
+        assert!(check_sum(&sums, "falta.tar.gz", &path).is_err());
+    }
+
+    #[test]
+    fn test_signature() {
+        let signing = SigningKey::from_bytes(&[7u8; 32]);
+        let public = hex::encode(signing.verifying_key().to_bytes());
+        let signature = hex::encode(signing.sign(b"sumas").to_bytes());
+
+        assert!(check_signature(&public, b"sumas", &signature).is_ok());
+        assert!(check_signature(&public, b"sumas cambiadas", &signature).is_err());
+        assert!(check_signature("abc", b"sumas", &signature).is_err());
+    }
+}